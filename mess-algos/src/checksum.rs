@@ -0,0 +1,61 @@
+//! Cyclic redundancy checks, to detect errors which slipped through decoding.
+
+/// Table-driven CRC of up to 32 bits, in reflected (least significant bit first) form,
+/// which covers the common CRC-32 variants.
+#[derive(Clone, Debug)]
+pub struct Crc {
+    /// reflected generator polynomial
+    pub poly: u32,
+    pub init: u32,
+    pub xor_out: u32,
+    table: Vec<u32>,
+}
+
+impl Crc {
+    pub fn new(poly: u32, init: u32, xor_out: u32) -> Self {
+        let table = (0..256u32)
+            .map(|byte| {
+                (0..8).fold(byte, |crc, _| if crc & 1 == 1 { crc >> 1 ^ poly } else { crc >> 1 })
+            })
+            .collect();
+        Crc { poly, init, xor_out, table }
+    }
+
+    /// CRC-32 of Ethernet, zip and png.
+    pub fn crc32() -> Self {
+        Crc::new(0xedb8_8320, 0xffff_ffff, 0xffff_ffff)
+    }
+
+    /// CRC-32C (Castagnoli) of iSCSI and ext4.
+    pub fn crc32c() -> Self {
+        Crc::new(0x82f6_3b78, 0xffff_ffff, 0xffff_ffff)
+    }
+
+    pub fn checksum(&self, bytes: &[u8]) -> u32 {
+        let crc = bytes.iter().fold(self.init, |crc, &byte| {
+            crc >> 8 ^ self.table[((crc ^ byte as u32) & 0xff) as usize]
+        });
+        crc ^ self.xor_out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check() {
+        // standard check values
+        assert_eq!(0xcbf4_3926, Crc::crc32().checksum(b"123456789"));
+        assert_eq!(0xe306_9283, Crc::crc32c().checksum(b"123456789"));
+        assert_eq!(0, Crc::crc32().checksum(b""));
+    }
+
+    #[test]
+    fn detects() {
+        let crc = Crc::crc32();
+        let original = crc.checksum(b"rust");
+        assert!(original != crc.checksum(b"rusT"));
+        assert!(original != crc.checksum(b"rust\0"));
+    }
+}
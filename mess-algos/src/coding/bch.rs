@@ -0,0 +1,259 @@
+//! Binary BCH codes, correcting up to `t` errors per block.
+
+use super::{Coding, Stats};
+use super::galois::Field;
+use bit_vec::BitVec;
+
+/// Narrow-sense primitive binary BCH code of length `n = 2^m - 1` with designed distance `2t + 1`.
+///
+/// Generator is the least common multiple of minimal polynomials of `α, α^2, …, α^2t`, and
+/// `k = n - deg g`.  For example `Bch::new(4, 2)` is (15,7) and `Bch::new(5, 3)` is (31,16).
+///
+/// Like `ReedSolomon`, encoding is systematic with parity first on the wire, and the last block
+/// is shortened to the data left.  Decoder finds the error locator with Berlekamp–Massey and its
+/// roots with Chien search; in the binary case error values are all ones, so no Forney step.
+///
+/// `Stats` are in blocks, as for `Hamming`.
+#[derive(Debug, Clone)]
+pub struct Bch {
+    pub m: usize,
+    pub t: usize,
+    /// highest degree first, coefficients are 0 or 1
+    generator: Vec<u8>,
+}
+
+impl Bch {
+    pub fn new(m: usize, t: usize) -> Self {
+        assert!(m >= 3 && m <= 8, "m must be in range [3..8]");
+        assert!(t >= 1 && 2 * t < (1 << m) - 1, "t must be in range [1..(2^m - 2) / 2]");
+        let generator = generator(&Field::new(m), t);
+        Bch { m, t, generator }
+    }
+
+    /// Block length, `n`.
+    pub fn block(&self) -> usize {
+        (1 << self.m) - 1
+    }
+
+    /// Data bits per block, `k`.
+    pub fn data(&self) -> usize {
+        self.block() - self.parity()
+    }
+
+    /// Parity bits per block, `n - k`.
+    pub fn parity(&self) -> usize {
+        self.generator.len() - 1
+    }
+
+    /// Parity bits for `data`: remainder of `data * x^(n-k)` divided by the generator.
+    fn encode_block(&self, data: &[u8]) -> Vec<u8> {
+        let mut remainder = data.to_vec();
+        remainder.resize(data.len() + self.parity(), 0);
+        for i in 0..data.len() {
+            if remainder[i] == 1 {
+                for (j, &g) in self.generator.iter().enumerate().skip(1) {
+                    remainder[i + j] ^= g;
+                }
+            }
+        }
+        remainder.split_off(data.len())
+    }
+
+    /// Correct `codeword` (data followed by parity, highest degree first) in place.
+    ///
+    /// Returns the number of corrected bits, or `None` if there are too many errors.
+    fn decode_block(&self, field: &Field, codeword: &mut [u8]) -> Option<usize> {
+        let syndromes: Vec<u8> = (1..=2 * self.t)
+            .map(|j| field.eval(codeword, field.pow(j as isize)))
+            .collect();
+        if syndromes.iter().all(|&s| s == 0) {
+            return Some(0);
+        }
+
+        let locator = field.locator(&syndromes)?;
+        let positions = field.roots(&locator, codeword.len())?;
+        for &i in positions.iter() {
+            codeword[i] ^= 1;
+        }
+        Some(positions.len())
+    }
+}
+
+/// Product of distinct minimal polynomials of `α^1 … α^2t`.
+fn generator(field: &Field, t: usize) -> Vec<u8> {
+    let mut generator = vec![1];
+    let mut seen = vec![false; field.order];
+
+    for i in 1..=2 * t {
+        if seen[i] {
+            continue;
+        }
+        // minimal polynomial of α^i has roots α^i, α^2i, α^4i, … (cyclotomic coset)
+        let mut minimal = vec![1];
+        let mut j = i;
+        while !seen[j] {
+            seen[j] = true;
+            minimal = multiply(field, &minimal, &[1, field.pow(j as isize)]);
+            j = 2 * j % field.order;
+        }
+        generator = multiply(field, &generator, &minimal);
+    }
+    generator
+}
+
+/// Product of polynomials, highest degree first.
+fn multiply(field: &Field, a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut product = vec![0; a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            product[i + j] ^= field.mul(x, y);
+        }
+    }
+    product
+}
+
+fn read_bits(input: &BitVec, start: usize, end: usize) -> Vec<u8> {
+    (start..end).map(|i| input.get(i).unwrap() as u8).collect()
+}
+
+impl Coding for Bch {
+    fn encode(&self, input: BitVec) -> BitVec {
+        let len = input.len();
+        let mut result = BitVec::with_capacity(len * self.block() / self.data() + self.block());
+
+        for start in (0..len).step_by(self.data()) {
+            let end = len.min(start + self.data());
+            let data = read_bits(&input, start, end);
+            let parity = self.encode_block(&data);
+            result.extend(parity.iter().chain(data.iter()).map(|&bit| bit == 1));
+        }
+
+        result
+    }
+
+    fn decode(&self, input: BitVec) -> (BitVec, Stats) {
+        let field = Field::new(self.m);
+        let mut stats = Stats::new();
+        let len = input.len();
+        let parity = self.parity();
+        let mut result = BitVec::with_capacity(len);
+        let mut offset = 0;
+
+        while offset < len {
+            let end = len.min(offset + self.block());
+            if end - offset <= parity {
+                // not even the parity is complete, nothing to decode
+                stats.detected += 1;
+                break;
+            }
+
+            let mut codeword = read_bits(&input, offset + parity, end);
+            codeword.extend(read_bits(&input, offset, offset + parity));
+
+            match self.decode_block(&field, &mut codeword) {
+                Some(0) => {}
+                Some(_) => {
+                    stats.detected += 1;
+                    stats.corrected += 1;
+                }
+                None => stats.detected += 1,
+            }
+            let data = end - offset - parity;
+            result.extend(codeword[..data].iter().map(|&bit| bit == 1));
+            offset = end;
+        }
+
+        (result, stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use coding::message;
+
+    #[test]
+    fn parameters() {
+        let bch = Bch::new(4, 2);
+        assert_eq!((15, 7), (bch.block(), bch.data()));
+        // x^8 + x^7 + x^6 + x^4 + 1
+        assert_eq!(vec![1, 1, 1, 0, 1, 0, 0, 0, 1], bch.generator);
+
+        let bch = Bch::new(5, 3);
+        assert_eq!((31, 16), (bch.block(), bch.data()));
+        let bch = Bch::new(8, 4);
+        assert_eq!((255, 223), (bch.block(), bch.data()));
+        // t = 1 is Hamming
+        let bch = Bch::new(3, 1);
+        assert_eq!((7, 4), (bch.block(), bch.data()));
+    }
+
+    #[test]
+    fn reverse() {
+        for &(m, t) in [(3, 1), (4, 2), (5, 3), (6, 5), (8, 4)].iter() {
+            let bch = Bch::new(m, t);
+            for &len in [0, 1, 6, 7, 16, 100, 3000].iter() {
+                let msg = message(len);
+                let code = bch.encode(msg.clone());
+                let blocks = (len + bch.data() - 1) / bch.data();
+                assert_eq!(len + bch.parity() * blocks, code.len());
+
+                let (decoded, stats) = bch.decode(code);
+                assert_eq!(msg, decoded);
+                assert_eq!(0, stats.detected);
+            }
+        }
+    }
+
+    #[test]
+    fn t_errors() {
+        let bch = Bch::new(5, 3);
+        let msg = message(16 * 3 + 5);
+        let code = bch.encode(msg.clone());
+
+        // every pair, and a few triples, in the first block; tail block is shortened to 20 bits
+        for i in 0..31 {
+            for j in i + 1..31 {
+                for &k in [None, Some(93), Some(100)].iter() {
+                    let mut noisy = code.clone();
+                    for e in Some(i).into_iter().chain(Some(j)).chain(k) {
+                        let bit = noisy[e];
+                        noisy.set(e, !bit);
+                    }
+                    let (decoded, stats) = bch.decode(noisy);
+                    assert_eq!(msg, decoded);
+                    assert_eq!(0, stats.uncorrectable());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn too_many_errors() {
+        let bch = Bch::new(4, 2);
+        let msg = message(7);
+        let code = bch.encode(msg.clone());
+
+        let mut failed = 0;
+        for i in 0..15 {
+            for j in i + 1..15 {
+                for k in j + 1..15 {
+                    let mut noisy = code.clone();
+                    for &e in [i, j, k].iter() {
+                        let bit = noisy[e];
+                        noisy.set(e, !bit);
+                    }
+                    let (decoded, stats) = bch.decode(noisy);
+                    // 3 errors are either detected, or miscorrected into another codeword
+                    assert_eq!(1, stats.detected);
+                    if stats.uncorrectable() > 0 {
+                        failed += 1;
+                    } else {
+                        assert!(decoded != msg);
+                    }
+                }
+            }
+        }
+        assert!(failed > 0);
+    }
+}
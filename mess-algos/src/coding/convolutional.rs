@@ -0,0 +1,228 @@
+//! Rate 1/2 convolutional code with hard-decision Viterbi decoding.
+
+use std::collections::VecDeque;
+
+use super::{Coding, Stats};
+use bit_vec::BitVec;
+
+/// Convolutional code of rate 1/2: every input bit enters a shift register of `constraint`
+/// bits, and two output bits are parities of the register masked by each of `generators`.
+///
+/// Register starts at zero, and input is followed by `constraint - 1` zero bits, so that the
+/// encoder ends in zero state too.  Output is `2 * (len + constraint - 1)` bits.
+///
+/// Decoder is Viterbi: the most likely path through the trellis, i.e. the codeword nearest
+/// to the received bits in Hamming distance.  Decisions are traced back in windows of
+/// `TRACEBACK * constraint` steps, to keep memory bounded on long inputs.
+///
+/// There are no blocks here, so `Stats` count trellis steps, i.e. pairs of received bits,
+/// that differ from the decoded codeword, all of them corrected: errors beyond the correction
+/// power are not detected.
+#[derive(Debug, Clone, Copy)]
+pub struct Convolutional {
+    pub constraint: usize,
+    pub generators: (u32, u32),
+}
+
+/// Traceback depth in constraint lengths.
+const TRACEBACK: usize = 5;
+
+impl Convolutional {
+    pub fn new(constraint: usize, generators: (u32, u32)) -> Self {
+        assert!(constraint >= 2 && constraint <= 9, "constraint must be in range [2..9]");
+        for &g in [generators.0, generators.1].iter() {
+            assert!(g != 0 && g < 1 << constraint, "generator must be a nonzero {} bit mask", constraint);
+        }
+        Convolutional { constraint, generators }
+    }
+
+    fn states(&self) -> usize {
+        1 << (self.constraint - 1)
+    }
+
+    /// Output pair for every register value.
+    fn outputs(&self) -> Vec<(bool, bool)> {
+        (0..1u32 << self.constraint)
+            .map(|register| {
+                ((register & self.generators.0).count_ones() % 2 == 1,
+                 (register & self.generators.1).count_ones() % 2 == 1)
+            })
+            .collect()
+    }
+}
+
+impl Default for Convolutional {
+    /// Constraint length 7 with generators 171, 133 (octal), as used by Voyager and 802.11.
+    fn default() -> Self {
+        Convolutional::new(7, (0o171, 0o133))
+    }
+}
+
+impl Coding for Convolutional {
+    fn encode(&self, input: BitVec) -> BitVec {
+        let outputs = self.outputs();
+        let mask = self.states() - 1;
+        let tail = self.constraint - 1;
+        let mut result = BitVec::with_capacity(2 * (input.len() + tail));
+        let mut state = 0;
+
+        for bit in input.iter().chain((0..tail).map(|_| false)) {
+            let register = state << 1 | bit as usize;
+            let (a, b) = outputs[register];
+            result.push(a);
+            result.push(b);
+            state = register & mask;
+        }
+
+        result
+    }
+
+    fn decode(&self, input: BitVec) -> (BitVec, Stats) {
+        let mut stats = Stats::new();
+        let outputs = self.outputs();
+        let states = self.states();
+        let high = self.constraint - 2;
+        let depth = TRACEBACK * self.constraint;
+
+        let steps = input.len() / 2;
+        let len = steps.saturating_sub(self.constraint - 1);
+        if input.len() % 2 != 0 || steps < self.constraint - 1 {
+            stats.detected += 1;
+        }
+
+        const UNREACHABLE: u32 = ::std::u32::MAX / 2;
+        let mut metrics = vec![UNREACHABLE; states];
+        metrics[0] = 0;
+        let mut next = vec![0; states];
+        // for every step and state, the dropped bit of the better predecessor
+        let mut decisions: VecDeque<BitVec> = VecDeque::with_capacity(2 * depth);
+        let mut result = BitVec::with_capacity(len);
+
+        let trace = |decisions: &VecDeque<BitVec>, mut state: usize, n: usize| -> BitVec {
+            // bits in reverse order, of all the steps in `decisions`, the oldest `n` returned
+            let mut bits: Vec<bool> = decisions.iter()
+                .rev()
+                .map(|decision| {
+                    let bit = state & 1 == 1;
+                    state = state >> 1 | (decision[state] as usize) << high;
+                    bit
+                })
+                .collect();
+            bits.reverse();
+            bits.truncate(n);
+            bits.into_iter().collect()
+        };
+
+        for step in 0..steps {
+            let received = (input[2 * step], input[2 * step + 1]);
+            let mut decision = BitVec::from_elem(states, false);
+
+            for state in 0..states {
+                let bit = state & 1;
+                let mut best = UNREACHABLE;
+                for dropped in 0..2 {
+                    let previous = state >> 1 | dropped << high;
+                    let (a, b) = outputs[previous << 1 | bit];
+                    let metric = metrics[previous] + (a != received.0) as u32 + (b != received.1) as u32;
+                    if metric < best {
+                        best = metric;
+                        decision.set(state, dropped == 1);
+                    }
+                }
+                next[state] = best;
+            }
+
+            let min = *next.iter().min().unwrap();
+            for (metric, &n) in metrics.iter_mut().zip(next.iter()) {
+                *metric = (n - min).min(UNREACHABLE);
+            }
+            decisions.push_back(decision);
+
+            if decisions.len() == 2 * depth {
+                let best = (0..states).min_by_key(|&s| metrics[s]).unwrap();
+                let n = depth.min(len - result.len());
+                result.extend(trace(&decisions, best, n).iter());
+                decisions.drain(..depth);
+            }
+        }
+
+        // terminated in zero state
+        let n = len - result.len();
+        result.extend(trace(&decisions, 0, n).iter());
+
+        let reencoded = self.encode(result.clone());
+        let errors = (0..steps)
+            .filter(|&step| (2 * step..2 * step + 2).any(|i| reencoded.get(i) != input.get(i)))
+            .count() as u32;
+        stats.detected += errors;
+        stats.corrected += errors;
+
+        (result, stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use coding::message;
+
+    #[test]
+    fn encode() {
+        // K = 3, generators 7 and 5: the textbook example
+        let code = Convolutional::new(3, (0b111, 0b101));
+        let encoded = code.encode(BitVec::from_bytes(&[0b1011_0000]).iter().take(4).collect());
+        let expected = "11 10 00 01 01 11";
+        assert_eq!(expected.replace(" ", ""),
+                   encoded.iter().map(|b| if b { '1' } else { '0' }).collect::<String>());
+    }
+
+    #[test]
+    fn reverse() {
+        for &code in [Convolutional::default(), Convolutional::new(3, (0b111, 0b101)),
+                      Convolutional::new(9, (0o561, 0o753))].iter() {
+            for &len in [0, 1, 2, 10, 100, 1000].iter() {
+                let msg = message(len);
+                let encoded = code.encode(msg.clone());
+                assert_eq!(2 * (len + code.constraint - 1), encoded.len());
+
+                let (decoded, stats) = code.decode(encoded);
+                assert_eq!(msg, decoded);
+                assert_eq!(0, stats.detected);
+            }
+        }
+    }
+
+    #[test]
+    fn scattered_errors() {
+        let code = Convolutional::default();
+        let msg = message(2000);
+        let mut encoded = code.encode(msg.clone());
+
+        // free distance of this code is 10, so errors far enough apart are all corrected
+        let mut flipped = 0;
+        for i in (7..encoded.len()).step_by(41) {
+            let bit = encoded[i];
+            encoded.set(i, !bit);
+            flipped += 1;
+        }
+        let (decoded, stats) = code.decode(encoded);
+        assert_eq!(msg, decoded);
+        assert_eq!((flipped, flipped), (stats.detected, stats.corrected));
+    }
+
+    #[test]
+    fn stats_per_step() {
+        let code = Convolutional::default();
+        let msg = message(100);
+        let mut encoded = code.encode(msg.clone());
+
+        // both bits of a single step
+        for &i in [40, 41].iter() {
+            let bit = encoded[i];
+            encoded.set(i, !bit);
+        }
+        let (decoded, stats) = code.decode(encoded);
+        assert_eq!(msg, decoded);
+        assert_eq!((1, 1, 0), (stats.detected, stats.corrected, stats.uncorrectable()));
+    }
+}
@@ -0,0 +1,148 @@
+//! Extended Hamming (8/4), SECDED: single error correction, double error detection.
+
+use super::{Coding, Stats};
+use bit_vec::BitVec;
+
+/// Hamming (7,4) followed by an overall parity bit.
+///
+/// Codeword is `p1 p2 d0 p3 d1 d2 d3 p`, where `p` makes the parity of all 8 bits even.
+/// Syndrome of the inner code locates a single error, and `p` tells single errors (odd number
+/// of flips) from double ones (even), which plain `Hamming` would "correct" into a wrong word.
+///
+/// Last block may carry less than 4 data bits; it is truncated right after its last data bit,
+/// as in `Hamming`, and still followed by `p`.
+#[derive(Debug)]
+pub struct ExtendedHamming;
+
+/// Positions of data bits inside a block.
+const DATA: [usize; 4] = [2, 4, 5, 6];
+
+fn parity(bits: &[bool]) -> bool {
+    bits.iter().fold(false, |p, &b| p ^ b)
+}
+
+/// 1-based position of a single flipped bit among the first 7, or 0.
+fn syndrome(b: &[bool; 7]) -> usize {
+    (b[0] ^ b[2] ^ b[4] ^ b[6]) as usize
+        | ((b[1] ^ b[2] ^ b[5] ^ b[6]) as usize) << 1
+        | ((b[3] ^ b[4] ^ b[5] ^ b[6]) as usize) << 2
+}
+
+impl Coding for ExtendedHamming {
+    fn encode(&self, input: BitVec) -> BitVec {
+        let len = input.len();
+        let mut result = BitVec::with_capacity(2 * len + 4);
+
+        for start in (0..len).step_by(4) {
+            let k = (len - start).min(4);
+            let mut b = [false; 7];
+            for (j, &pos) in DATA.iter().enumerate().take(k) {
+                b[pos] = input.get(start + j).unwrap();
+            }
+            b[0] = b[2] ^ b[4] ^ b[6];
+            b[1] = b[2] ^ b[5] ^ b[6];
+            b[3] = b[4] ^ b[5] ^ b[6];
+
+            let used = DATA[k - 1] + 1;
+            for &bit in &b[..used] {
+                result.push(bit);
+            }
+            result.push(parity(&b[..used]));
+        }
+
+        result
+    }
+
+    fn decode(&self, input: BitVec) -> (BitVec, Stats) {
+        let mut stats = Stats::new();
+        let len = input.len();
+        let mut result = BitVec::with_capacity(len / 2 + 4);
+        let mut offset = 0;
+
+        while offset < len {
+            let used = (len - offset).min(8) - 1;
+            let mut b = [false; 7];
+            for (j, bit) in b.iter_mut().enumerate().take(used) {
+                *bit = input.get(offset + j).unwrap();
+            }
+            let odd = parity(&b[..used]) ^ input.get(offset + used).unwrap();
+
+            match (syndrome(&b), odd) {
+                (0, false) => {}
+                // overall parity bit itself
+                (0, true) => {
+                    stats.detected += 1;
+                    stats.corrected += 1;
+                }
+                (s, true) if s <= used => {
+                    b[s - 1] = !b[s - 1];
+                    stats.detected += 1;
+                    stats.corrected += 1;
+                }
+                // even number of flips, or garbage in a truncated block
+                _ => stats.detected += 1,
+            }
+
+            for &pos in DATA.iter().take_while(|&&pos| pos < used) {
+                result.push(b[pos]);
+            }
+            offset += used + 1;
+        }
+
+        (result, stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_coding(msg: &BitVec) -> BitVec {
+        let code = ExtendedHamming.encode(msg.clone());
+        let (decoded, stats) = ExtendedHamming.decode(code.clone());
+        assert_eq!(*msg, decoded);
+        assert_eq!(0, stats.detected);
+        code
+    }
+
+    #[test]
+    fn reverse() {
+        for len in 0..17 {
+            let msg: BitVec = (0..len).map(|i| i % 3 == 0).collect();
+            let code = test_coding(&msg);
+            assert_eq!(8 * (len / 4) + [0, 4, 6, 7][len % 4], code.len());
+        }
+    }
+
+    #[test]
+    fn single_error() {
+        let msg = BitVec::from_bytes(&[0b1011_0010, 0b0100_1110]);
+        let code = ExtendedHamming.encode(msg.clone());
+
+        for i in 0..code.len() {
+            let mut noisy = code.clone();
+            noisy.set(i, !code[i]);
+            let (decoded, stats) = ExtendedHamming.decode(noisy);
+            assert_eq!(msg, decoded);
+            assert_eq!((1, 1), (stats.detected, stats.corrected));
+            assert_eq!(0, stats.uncorrectable());
+        }
+    }
+
+    #[test]
+    fn double_error() {
+        let msg = BitVec::from_bytes(&[0b1011_0010]);
+        let code = ExtendedHamming.encode(msg.clone());
+
+        for i in 0..8 {
+            for j in i + 1..8 {
+                let mut noisy = code.clone();
+                noisy.set(i, !code[i]);
+                noisy.set(j, !code[j]);
+                let (_, stats) = ExtendedHamming.decode(noisy);
+                assert_eq!((1, 0), (stats.detected, stats.corrected));
+                assert_eq!(1, stats.uncorrectable());
+            }
+        }
+    }
+}
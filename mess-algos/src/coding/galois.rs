@@ -0,0 +1,153 @@
+//! Arithmetic in GF(2^m), and decoding steps shared by the codes built on it.
+
+/// Primitive polynomials for `m` in `[2..8]`, with the `x^m` term.
+const PRIMITIVE: [usize; 9] = [0, 0, 0b111, 0b1011, 0b10011, 0b100101, 0b1000011, 0b10001001, 0x11d];
+
+/// GF(2^m) through tables of powers and logarithms of the primitive element `α = x`.
+///
+/// Elements are bytes, so `m` is at most 8.
+pub struct Field {
+    /// number of nonzero elements, `2^m - 1`, which is also the order of `α`
+    pub order: usize,
+    exp: Vec<u8>,
+    log: Vec<usize>,
+}
+
+impl Field {
+    pub fn new(m: usize) -> Self {
+        assert!(m >= 2 && m <= 8, "m must be in range [2..8]");
+        let order = (1 << m) - 1;
+        let mut exp = vec![0; 2 * order];
+        let mut log = vec![0; order + 1];
+
+        let mut x = 1;
+        for i in 0..order {
+            exp[i] = x as u8;
+            log[x] = i;
+            x <<= 1;
+            if x & (1 << m) != 0 {
+                x ^= PRIMITIVE[m];
+            }
+        }
+        for i in order..2 * order {
+            exp[i] = exp[i - order];
+        }
+        Field { order, exp, log }
+    }
+
+    pub fn mul(&self, a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            return 0;
+        }
+        self.exp[self.log[a as usize] + self.log[b as usize]]
+    }
+
+    pub fn div(&self, a: u8, b: u8) -> u8 {
+        assert!(b != 0, "division by zero in GF(2^m)");
+        if a == 0 {
+            return 0;
+        }
+        self.exp[self.log[a as usize] + self.order - self.log[b as usize]]
+    }
+
+    /// `α^power`, power may be negative.
+    pub fn pow(&self, power: isize) -> u8 {
+        self.exp[power.rem_euclid(self.order as isize) as usize]
+    }
+
+    /// Value of a polynomial with coefficients from the highest degree at `x`.
+    pub fn eval(&self, poly: &[u8], x: u8) -> u8 {
+        poly.iter().fold(0, |acc, &c| self.mul(acc, x) ^ c)
+    }
+
+    /// Value of a polynomial with coefficients from the lowest degree at `x`.
+    pub fn eval_rev(&self, poly: &[u8], x: u8) -> u8 {
+        poly.iter().rev().fold(0, |acc, &c| self.mul(acc, x) ^ c)
+    }
+
+    /// Berlekamp–Massey: error locator `Λ(x)` for `syndromes`, coefficients from the lowest degree.
+    ///
+    /// Returns `None` if the locator is inconsistent, i.e. there are more errors than
+    /// `syndromes.len() / 2`.
+    pub fn locator(&self, syndromes: &[u8]) -> Option<Vec<u8>> {
+        let mut locator = vec![1];
+        let mut previous = vec![1];
+        let (mut errors, mut shift, mut last) = (0, 1, 1);
+
+        for i in 0..syndromes.len() {
+            let mut discrepancy = syndromes[i];
+            for j in 1..locator.len().min(i + 1) {
+                discrepancy ^= self.mul(locator[j], syndromes[i - j]);
+            }
+            if discrepancy == 0 {
+                shift += 1;
+                continue;
+            }
+            let factor = self.div(discrepancy, last);
+            let mut next = locator.clone();
+            next.resize(locator.len().max(previous.len() + shift), 0);
+            for (j, &p) in previous.iter().enumerate() {
+                next[j + shift] ^= self.mul(factor, p);
+            }
+            if 2 * errors <= i {
+                previous = locator;
+                errors = i + 1 - errors;
+                last = discrepancy;
+                shift = 1;
+            } else {
+                shift += 1;
+            }
+            locator = next;
+        }
+
+        while locator.last() == Some(&0) {
+            locator.pop();
+        }
+        if errors > syndromes.len() / 2 || locator.len() != errors + 1 {
+            return None;
+        }
+        Some(locator)
+    }
+
+    /// Chien search: indices of errors in a codeword of `len` symbols, highest degree first,
+    /// i.e. index `i` stands for `α^(len - 1 - i)`.
+    ///
+    /// Returns `None` unless there are exactly as many roots as the degree of `locator`, which
+    /// happens when errors point past the end of a shortened codeword.
+    pub fn roots(&self, locator: &[u8], len: usize) -> Option<Vec<usize>> {
+        let positions: Vec<usize> = (0..len)
+            .filter(|&i| self.eval_rev(locator, self.pow(-((len - 1 - i) as isize))) == 0)
+            .collect();
+        if positions.len() + 1 != locator.len() {
+            return None;
+        }
+        Some(positions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn field() {
+        for m in 2..9 {
+            let field = Field::new(m);
+            // α generates all nonzero elements
+            let mut powers: Vec<u8> = (0..field.order).map(|i| field.pow(i as isize)).collect();
+            powers.sort();
+            powers.dedup();
+            assert_eq!(field.order, powers.len());
+            assert_eq!(1, field.pow(field.order as isize));
+
+            for a in 1..=field.order as u8 {
+                assert_eq!(1, field.div(a, a));
+                for b in 1..=field.order as u8 {
+                    assert_eq!(a, field.div(field.mul(a, b), b));
+                }
+            }
+        }
+        // x * x^7 = x^4 + x^3 + x^2 + 1
+        assert_eq!(0x1d, Field::new(8).mul(2, 0x80));
+    }
+}
@@ -0,0 +1,161 @@
+//! Hamming (2^r - 1, 2^r - r - 1) for any `r`: (7,4), (15,11), (31,26), (63,57) etc.
+
+use super::{Coding, Stats};
+use bit_vec::BitVec;
+
+/// Hamming code with `r` parity bits per block of `2^r - 1` bits.
+///
+/// Positions in a block are numbered from 1.  Parity bits sit at powers of two, and bit `2^i`
+/// makes the parity of all positions with `i`-th bit set even, so the syndrome of a block with
+/// a single error is the position of that error.  Data bits fill the remaining positions in order.
+///
+/// Last block is truncated right after its last data bit.  With `r = 3` the output is the same
+/// as that of `Hamming`.
+#[derive(Debug, Clone, Copy)]
+pub struct GenericHamming {
+    pub r: usize,
+}
+
+impl GenericHamming {
+    pub fn new(r: usize) -> Self {
+        assert!(r >= 2 && r <= 16, "r must be in range [2..16]");
+        GenericHamming { r }
+    }
+
+    /// Block length, `n`.
+    pub fn block(&self) -> usize {
+        (1 << self.r) - 1
+    }
+
+    /// Data bits per block, `k`.
+    pub fn data(&self) -> usize {
+        self.block() - self.r
+    }
+
+    /// 1-based positions of data bits inside a block.
+    fn data_positions(&self) -> Vec<usize> {
+        (1..=self.block()).filter(|j| !j.is_power_of_two()).collect()
+    }
+}
+
+/// XOR of 1-based positions of set bits.
+fn syndrome(block: &[bool]) -> usize {
+    block.iter()
+        .enumerate()
+        .filter(|&(_, &bit)| bit)
+        .fold(0, |s, (j, _)| s ^ (j + 1))
+}
+
+impl Coding for GenericHamming {
+    fn encode(&self, input: BitVec) -> BitVec {
+        let len = input.len();
+        let positions = self.data_positions();
+        let mut result = BitVec::with_capacity(len * self.block() / self.data() + self.block());
+        let mut block = vec![false; self.block()];
+
+        for start in (0..len).step_by(self.data()) {
+            let k = (len - start).min(self.data());
+            for bit in block.iter_mut() {
+                *bit = false;
+            }
+            for (i, &j) in positions.iter().enumerate().take(k) {
+                block[j - 1] = input.get(start + i).unwrap();
+            }
+
+            // parity bits are zero yet, so the syndrome tells which of them must be set
+            let s = syndrome(&block);
+            for i in 0..self.r {
+                block[(1 << i) - 1] = s & (1 << i) != 0;
+            }
+
+            let used = positions[k - 1];
+            result.extend(block[..used].iter().cloned());
+        }
+
+        result
+    }
+
+    fn decode(&self, input: BitVec) -> (BitVec, Stats) {
+        let mut stats = Stats::new();
+        let len = input.len();
+        let positions = self.data_positions();
+        let mut result = BitVec::with_capacity(len);
+        let mut offset = 0;
+
+        while offset < len {
+            let used = (len - offset).min(self.block());
+            let mut block: Vec<bool> = (offset..offset + used).map(|j| input.get(j).unwrap()).collect();
+
+            let s = syndrome(&block);
+            if s != 0 {
+                stats.detected += 1;
+                // truncated block can not have an error past its end
+                if s <= used {
+                    block[s - 1] = !block[s - 1];
+                    stats.corrected += 1;
+                }
+            }
+
+            for &j in positions.iter().take_while(|&&j| j <= used) {
+                result.push(block[j - 1]);
+            }
+            offset += used;
+        }
+
+        (result, stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use coding::hamming::Hamming;
+    use coding::message;
+
+    #[test]
+    fn parameters() {
+        let nk: Vec<(usize, usize)> = (2..7)
+            .map(|r| GenericHamming::new(r))
+            .map(|h| (h.block(), h.data()))
+            .collect();
+        assert_eq!(vec![(3, 1), (7, 4), (15, 11), (31, 26), (63, 57)], nk);
+    }
+
+    #[test]
+    fn same_as_hamming() {
+        for len in 0..20 {
+            let msg = message(len);
+            assert_eq!(Hamming.encode(msg.clone()), GenericHamming::new(3).encode(msg));
+        }
+    }
+
+    #[test]
+    fn reverse() {
+        for r in 2..9 {
+            let hamming = GenericHamming::new(r);
+            for &len in [0, 1, 5, 11, 57, 300, 1000].iter() {
+                let msg = message(len);
+                let (decoded, stats) = hamming.decode(hamming.encode(msg.clone()));
+                assert_eq!(msg, decoded);
+                assert_eq!(0, stats.detected);
+            }
+        }
+    }
+
+    #[test]
+    fn single_error() {
+        for r in 3..7 {
+            let hamming = GenericHamming::new(r);
+            let msg = message(3 * hamming.data() + 2);
+            let code = hamming.encode(msg.clone());
+
+            for i in 0..code.len() {
+                let mut noisy = code.clone();
+                noisy.set(i, !code[i]);
+                let (decoded, stats) = hamming.decode(noisy);
+                assert_eq!(msg, decoded);
+                assert_eq!((1, 1), (stats.detected, stats.corrected));
+            }
+        }
+    }
+}
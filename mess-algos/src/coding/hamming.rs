@@ -1,7 +1,7 @@
 //! Hamming (4/7)
 
 
-use super::{Coding, Stats};
+use super::{Coding, SoftDecoding, Stats, hard_decision};
 use bit_vec::BitVec;
 
 #[derive(Debug)]
@@ -66,6 +66,48 @@ impl Coding for Hamming {
     }
 }
 
+impl SoftDecoding for Hamming {
+    /// Maximum likelihood: the codeword with the greatest correlation with the ratios.
+    fn decode_soft(&self, input: &[f64]) -> (BitVec, Stats) {
+        let mut stats = Stats::new();
+        let mut result = BitVec::with_capacity(input.len() * 4 / 7 + 4);
+
+        for block in input.chunks(7) {
+            // truncated blocks carry less data, as in `decode`
+            let k = match block.len() {
+                7 => 4,
+                len if len > 3 => len - 3,
+                3 => 1,
+                _ => 0,
+            };
+            let candidates = (0..1usize << k).map(|data| {
+                let bits: BitVec = (0..k).map(|i| data >> (k - 1 - i) & 1 == 1).collect();
+                let codeword = self.encode(bits.clone());
+                let score: f64 = codeword.iter()
+                    .zip(block.iter())
+                    .map(|(bit, &llr)| if bit { -llr } else { llr })
+                    .sum();
+                (bits, codeword, score)
+            });
+            let (bits, codeword, _) = candidates.fold(None, |best: Option<(BitVec, BitVec, f64)>, candidate| {
+                match best {
+                    Some(ref best) if best.2 >= candidate.2 => Some(best.clone()),
+                    _ => Some(candidate),
+                }
+            }).unwrap();
+
+            let received = hard_decision(&block[..codeword.len()]);
+            if received != codeword {
+                stats.detected += 1;
+                stats.corrected += 1;
+            }
+            result.extend(bits.iter());
+        }
+
+        (result, stats)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -98,6 +140,28 @@ mod test {
         assert_eq!(bv2str(Hamming.decode(str2bv("0110101")).0), "0101");
     }
 
+    #[test]
+    fn test_decode_soft() {
+        let llr = |bits: &str, confidence: f64| -> Vec<f64> {
+            bits.chars().map(|ch| if ch == '1' { -confidence } else { confidence }).collect()
+        };
+        for &(code, data) in [("10000111000011", "00110011"), ("1000011100001110011", "0011001101"),
+                              ("000", "0"), ("10011", "01"), ("100110", "010"), ("0100101", "0101")].iter() {
+            let (decoded, stats) = Hamming.decode_soft(&llr(code, 3.0));
+            assert_eq!(data, bv2str(decoded));
+            assert_eq!(0, stats.detected);
+        }
+
+        // two weak errors are beyond hard decoding, but not beyond soft one
+        let mut input = llr("0000000", 4.0);
+        input[1] = -0.5;
+        input[5] = -0.5;
+        assert!(bv2str(Hamming.decode(hard_decision(&input)).0) != "0000");
+        let (decoded, stats) = Hamming.decode_soft(&input);
+        assert_eq!("0000", bv2str(decoded));
+        assert_eq!((1, 1), (stats.detected, stats.corrected));
+    }
+
     fn bv2str(input: BitVec) -> String {
         let mut result = String::new();
         for i in input.iter() {
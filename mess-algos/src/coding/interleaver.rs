@@ -0,0 +1,146 @@
+//! Interleavers, spreading burst errors over many codewords.
+
+use super::{Coding, Stats};
+use bit_vec::BitVec;
+
+/// Permutation of encoded bits.  Neither adds any bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interleaver {
+    /// Bits are written into a matrix by rows of `columns` bits and read by columns, `rows` at
+    /// a time, so a burst of up to `rows` bits hits every row at most once.  Input not filling
+    /// the last matrix makes a matrix with fewer rows.
+    Block { rows: usize, columns: usize },
+    /// Forney's convolutional interleaver: bit `t` goes to branch `t % branches`, which delays
+    /// it by `branch * delay * branches` positions.  Positions the delay lines would fill
+    /// with padding are dropped, so that the output is a permutation of the input.
+    Convolutional { branches: usize, delay: usize },
+}
+
+impl Interleaver {
+    /// `permutation[i]` is the position in the input of `i`-th output bit.
+    pub fn permutation(&self, len: usize) -> Vec<usize> {
+        match *self {
+            Interleaver::Block { rows, columns } => {
+                assert!(rows > 0 && columns > 0, "interleaver matrix must not be empty");
+                let mut permutation = Vec::with_capacity(len);
+                for start in (0..len).step_by(rows * columns) {
+                    let size = (len - start).min(rows * columns);
+                    let rows = (size + columns - 1) / columns;
+                    for c in 0..columns {
+                        for r in 0..rows {
+                            let i = r * columns + c;
+                            if i < size {
+                                permutation.push(start + i);
+                            }
+                        }
+                    }
+                }
+                permutation
+            }
+            Interleaver::Convolutional { branches, delay } => {
+                assert!(branches > 0, "interleaver must have at least one branch");
+                let mut permutation: Vec<usize> = (0..len).collect();
+                permutation.sort_by_key(|&t| t + (t % branches) * delay * branches);
+                permutation
+            }
+        }
+    }
+
+    pub fn interleave(&self, input: &BitVec) -> BitVec {
+        self.permutation(input.len()).into_iter().map(|i| input[i]).collect()
+    }
+
+    pub fn deinterleave(&self, input: &BitVec) -> BitVec {
+        let mut output = BitVec::from_elem(input.len(), false);
+        for (bit, i) in input.iter().zip(self.permutation(input.len())) {
+            output.set(i, bit);
+        }
+        output
+    }
+}
+
+/// Any `Coding` with its output interleaved, e.g. `Interleaved<Hamming>`.
+///
+/// Burst errors in the channel turn into scattered ones for the inner code, provided the
+/// interleaver is deeper than the bursts are long.
+#[derive(Debug, Clone)]
+pub struct Interleaved<C> {
+    pub coding: C,
+    pub interleaver: Interleaver,
+}
+
+impl<C: Coding> Interleaved<C> {
+    pub fn new(coding: C, interleaver: Interleaver) -> Self {
+        Interleaved { coding, interleaver }
+    }
+}
+
+impl<C: Coding> Coding for Interleaved<C> {
+    fn encode(&self, input: BitVec) -> BitVec {
+        self.interleaver.interleave(&self.coding.encode(input))
+    }
+
+    fn decode(&self, input: BitVec) -> (BitVec, Stats) {
+        self.coding.decode(self.interleaver.deinterleave(&input))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use coding::hamming::Hamming;
+    use coding::message;
+
+    #[test]
+    fn block() {
+        let interleaver = Interleaver::Block { rows: 2, columns: 3 };
+        assert_eq!(vec![0, 3, 1, 4, 2, 5, 6, 7], interleaver.permutation(8));
+        assert_eq!(vec![0, 3, 1, 4, 2], interleaver.permutation(5));
+    }
+
+    #[test]
+    fn convolutional() {
+        let interleaver = Interleaver::Convolutional { branches: 2, delay: 1 };
+        // keys: 0, 3, 2, 5, 4, 7
+        assert_eq!(vec![0, 2, 1, 4, 3, 5], interleaver.permutation(6));
+    }
+
+    #[test]
+    fn reverse() {
+        for &interleaver in [Interleaver::Block { rows: 7, columns: 10 },
+                             Interleaver::Block { rows: 1, columns: 1 },
+                             Interleaver::Convolutional { branches: 5, delay: 3 },
+                             Interleaver::Convolutional { branches: 1, delay: 0 }].iter() {
+            for &len in [0, 1, 13, 70, 1000].iter() {
+                let msg = message(len);
+                let mut permutation = interleaver.permutation(len);
+                permutation.sort();
+                assert_eq!((0..len).collect::<Vec<_>>(), permutation);
+                assert_eq!(msg, interleaver.deinterleave(&interleaver.interleave(&msg)));
+            }
+        }
+    }
+
+    #[test]
+    fn burst() {
+        let msg = message(400);
+        let plain = Hamming.encode(msg.clone());
+        let interleaved = Interleaved::new(Hamming, Interleaver::Block { rows: 10, columns: 7 });
+        let mut encoded = interleaved.encode(msg.clone());
+        assert_eq!(plain.len(), encoded.len());
+
+        // a burst of 10 bits breaks plain Hamming, but hits 10 different codewords once each
+        let mut broken = plain.clone();
+        for i in 100..110 {
+            let bit = broken[i];
+            broken.set(i, !bit);
+            let bit = encoded[i];
+            encoded.set(i, !bit);
+        }
+        assert!(msg != Hamming.decode(broken).0);
+
+        let (decoded, stats) = interleaved.decode(encoded);
+        assert_eq!(msg, decoded);
+        assert_eq!(10, stats.corrected);
+    }
+}
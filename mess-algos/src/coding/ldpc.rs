@@ -0,0 +1,530 @@
+//! Low-density parity-check codes with iterative decoding.
+
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+use super::{Coding, Stats};
+use bit_vec::BitVec;
+
+/// Sparse parity-check matrix `H`: a word `x` is a codeword iff `H x = 0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParityCheck {
+    /// number of columns, i.e. codeword length
+    pub n: usize,
+    /// variables (columns) of every check (row), ascending
+    pub checks: Vec<Vec<usize>>,
+    /// checks (rows) of every variable (column), ascending
+    pub variables: Vec<Vec<usize>>,
+}
+
+/// Errors of reading an alist file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    ExpectedMoreData,
+    InvalidNumber(String),
+    /// index out of range of the matrix, 1-based as in the file
+    InvalidIndex(usize),
+    /// column and row lists describe different matrices, or weights do not match
+    Inconsistent,
+}
+
+impl ParityCheck {
+    /// Matrix with `n` columns and given rows.
+    pub fn new(n: usize, mut checks: Vec<Vec<usize>>) -> Self {
+        let mut variables = vec![Vec::new(); n];
+        for (c, check) in checks.iter_mut().enumerate() {
+            check.sort();
+            check.dedup();
+            for &v in check.iter() {
+                assert!(v < n, "variable index out of range");
+                variables[v].push(c);
+            }
+        }
+        ParityCheck { n, checks, variables }
+    }
+
+    /// Number of rows.
+    pub fn m(&self) -> usize {
+        self.checks.len()
+    }
+
+    /// Regular matrix with `column` ones per column and `row` ones per row, after Gallager.
+    ///
+    /// Rows are split into `column` bands of `n / row` rows.  In the first band row `i` covers
+    /// columns `[i * row, (i + 1) * row)`, and every other band is the first one with columns
+    /// shuffled.  Shuffles are drawn from a generator seeded with `seed`, so both sides build
+    /// the same matrix from the same parameters.
+    pub fn regular(n: usize, column: usize, row: usize, seed: u32) -> Self {
+        assert!(column >= 2 && row > column, "row weight must be greater than column weight >= 2");
+        assert!(n % row == 0, "n must be a multiple of row weight");
+
+        let band = n / row;
+        let mut rng = XorShiftRng::from_seed([seed, 0x193a_6754, 0xa8a7_d469, 0x9783_0e05]);
+        let mut permutation: Vec<usize> = (0..n).collect();
+        let mut checks = Vec::with_capacity(band * column);
+
+        for b in 0..column {
+            if b > 0 {
+                rng.shuffle(&mut permutation);
+            }
+            for i in 0..band {
+                checks.push(permutation[i * row..(i + 1) * row].to_vec());
+            }
+        }
+        ParityCheck::new(n, checks)
+    }
+
+    /// Parse a matrix in MacKay's alist format:
+    ///
+    /// ```text
+    /// n m
+    /// max_column_weight max_row_weight
+    /// weight of every column
+    /// weight of every row
+    /// rows of every column, 1-based, padded with zeros to max_column_weight
+    /// columns of every row, 1-based, padded with zeros to max_row_weight
+    /// ```
+    ///
+    /// Padding zeros are optional, as in many published files.
+    pub fn from_alist(text: &str) -> Result<Self, Error> {
+        let mut numbers = text.split_whitespace()
+            .map(|s| s.parse::<usize>().map_err(|_| Error::InvalidNumber(s.into())));
+        let mut next = || numbers.next().unwrap_or(Err(Error::ExpectedMoreData));
+
+        let (n, m) = (next()?, next()?);
+        let (max_column, max_row) = (next()?, next()?);
+        let column_weights = (0..n).map(|_| next()).collect::<Result<Vec<_>, _>>()?;
+        let row_weights = (0..m).map(|_| next()).collect::<Result<Vec<_>, _>>()?;
+
+        // indices are never zero, so padding is just skipped
+        let mut lists = |weights: &[usize], max: usize, bound: usize| -> Result<Vec<Vec<usize>>, Error> {
+            let mut lists = Vec::with_capacity(weights.len());
+            for &weight in weights {
+                if weight > max {
+                    return Err(Error::Inconsistent);
+                }
+                let mut list = Vec::with_capacity(weight);
+                while list.len() < weight {
+                    match next()? {
+                        0 => continue,
+                        index if index > bound => return Err(Error::InvalidIndex(index)),
+                        index => list.push(index - 1),
+                    }
+                }
+                list.sort();
+                lists.push(list);
+            }
+            Ok(lists)
+        };
+        let columns = lists(&column_weights, max_column, m)?;
+        let rows = lists(&row_weights, max_row, n)?;
+
+        let matrix = ParityCheck::new(n, rows);
+        if matrix.variables != columns {
+            return Err(Error::Inconsistent);
+        }
+        Ok(matrix)
+    }
+
+    /// Matrix in alist format, with zero padding.
+    pub fn to_alist(&self) -> String {
+        fn line(items: &[usize], width: usize) -> String {
+            let mut items: Vec<String> = items.iter().map(|&i| (i + 1).to_string()).collect();
+            items.resize(width, "0".into());
+            items.join(" ") + "\n"
+        }
+        let max_column = self.variables.iter().map(Vec::len).max().unwrap_or(0);
+        let max_row = self.checks.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut text = format!("{} {}\n{} {}\n", self.n, self.m(), max_column, max_row);
+        let weights = |lists: &[Vec<usize>]| lists.iter().map(|l| l.len().to_string()).collect::<Vec<_>>().join(" ") + "\n";
+        text += &weights(&self.variables);
+        text += &weights(&self.checks);
+        for column in self.variables.iter() {
+            text += &line(column, max_column);
+        }
+        for row in self.checks.iter() {
+            text += &line(row, max_row);
+        }
+        text
+    }
+
+    /// Number of unsatisfied checks.
+    pub fn syndrome_weight(&self, word: &[bool]) -> usize {
+        self.checks.iter()
+            .filter(|check| check.iter().fold(false, |p, &v| p ^ word[v]))
+            .count()
+    }
+}
+
+/// Iterative decoding algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoder {
+    /// Gallager's hard-decision bit flipping: flip the bits in most unsatisfied checks.
+    BitFlipping,
+    /// Normalized min-sum approximation of belief propagation.
+    MinSum,
+}
+
+/// LDPC code defined by a parity-check matrix.
+///
+/// Matrix is brought to reduced row echelon form once, its pivot columns carry parity bits,
+/// and the rest carry data, so encoding is systematic and works for any full or rank
+/// deficient `H`: `k = n - rank(H)`.  Codeword bits are sent in column order.
+///
+/// Last block is shortened: data bits past the end of input are zeros, they are not sent,
+/// and decoder treats them as known.
+///
+/// `Stats` are in blocks: a block with a nonzero syndrome is detected, and corrected if the
+/// decoder converged to a codeword within `max_iterations`.  `iterations` and
+/// `syndrome_weight` are summed over all blocks.
+#[derive(Debug, Clone)]
+pub struct Ldpc {
+    pub matrix: ParityCheck,
+    pub decoder: Decoder,
+    pub max_iterations: usize,
+    /// pivot columns, one per independent row
+    parity: Vec<usize>,
+    /// remaining columns, in order
+    data: Vec<usize>,
+    /// for every parity bit, indices into `data` of bits it sums
+    equations: Vec<Vec<usize>>,
+    /// for every variable, its position in each of its checks
+    edges: Vec<Vec<usize>>,
+}
+
+/// Normalization of min-sum check messages.
+const SCALE: f32 = 0.75;
+/// Confidence of bits known for sure, i.e. shortened ones.
+const KNOWN: f32 = 1e6;
+
+impl Ldpc {
+    pub fn new(matrix: ParityCheck, decoder: Decoder, max_iterations: usize) -> Self {
+        let n = matrix.n;
+        let words = (n + 63) / 64;
+
+        // dense rows, Gauss–Jordan elimination over GF(2)
+        let mut rows: Vec<Vec<u64>> = matrix.checks.iter()
+            .map(|check| {
+                let mut row = vec![0; words];
+                for &v in check.iter() {
+                    row[v / 64] |= 1 << (v % 64);
+                }
+                row
+            })
+            .collect();
+        let get = |row: &[u64], column: usize| row[column / 64] >> (column % 64) & 1 == 1;
+
+        let mut parity = Vec::new();
+        let mut rank = 0;
+        for column in 0..n {
+            let pivot = match (rank..rows.len()).find(|&r| get(&rows[r], column)) {
+                Some(pivot) => pivot,
+                None => continue,
+            };
+            rows.swap(rank, pivot);
+            for r in 0..rows.len() {
+                if r != rank && get(&rows[r], column) {
+                    for w in 0..words {
+                        let x = rows[rank][w];
+                        rows[r][w] ^= x;
+                    }
+                }
+            }
+            parity.push(column);
+            rank += 1;
+        }
+        assert!(rank < n, "parity-check matrix must leave room for data");
+
+        let data: Vec<usize> = (0..n).filter(|c| parity.binary_search(c).is_err()).collect();
+        let equations = rows[..rank].iter()
+            .map(|row| (0..data.len()).filter(|&i| get(row, data[i])).collect())
+            .collect();
+        let edges = matrix.variables.iter()
+            .enumerate()
+            .map(|(v, checks)| {
+                checks.iter().map(|&c| matrix.checks[c].binary_search(&v).unwrap()).collect()
+            })
+            .collect();
+
+        Ldpc { matrix, decoder, max_iterations, parity, data, equations, edges }
+    }
+
+    /// Codeword length, `n`.
+    pub fn block(&self) -> usize {
+        self.matrix.n
+    }
+
+    /// Data bits per block, `k`.
+    pub fn data(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Which columns are sent in a block with `d` data bits.
+    fn sent(&self, d: usize) -> Vec<bool> {
+        let mut sent = vec![true; self.block()];
+        for &column in self.data[d..].iter() {
+            sent[column] = false;
+        }
+        sent
+    }
+
+    /// Hard-decision bit flipping, returns the number of iterations.
+    fn bit_flipping(&self, word: &mut [bool], sent: &[bool]) -> usize {
+        let matrix = &self.matrix;
+        for iteration in 0..self.max_iterations {
+            let unsatisfied: Vec<bool> = matrix.checks.iter()
+                .map(|check| check.iter().fold(false, |p, &v| p ^ word[v]))
+                .collect();
+            if unsatisfied.iter().all(|&u| !u) {
+                return iteration;
+            }
+            let votes: Vec<usize> = (0..matrix.n)
+                .map(|v| if sent[v] { matrix.variables[v].iter().filter(|&&c| unsatisfied[c]).count() } else { 0 })
+                .collect();
+            let max = *votes.iter().max().unwrap();
+            if max == 0 {
+                return self.max_iterations;
+            }
+            for v in 0..matrix.n {
+                if votes[v] == max {
+                    word[v] = !word[v];
+                }
+            }
+        }
+        self.max_iterations
+    }
+
+    /// Normalized min-sum, returns the number of iterations.
+    fn min_sum(&self, word: &mut [bool], sent: &[bool]) -> usize {
+        let matrix = &self.matrix;
+        let channel: Vec<f32> = (0..matrix.n)
+            .map(|v| match (sent[v], word[v]) {
+                (false, _) => KNOWN,
+                (true, false) => 1.0,
+                (true, true) => -1.0,
+            })
+            .collect();
+        // variable to check messages, parallel to `matrix.checks`
+        let mut to_check: Vec<Vec<f32>> = matrix.checks.iter()
+            .map(|check| check.iter().map(|&v| channel[v]).collect())
+            .collect();
+        let mut to_variable = to_check.clone();
+
+        for iteration in 0..self.max_iterations {
+            if matrix.syndrome_weight(word) == 0 {
+                return iteration;
+            }
+
+            for (c, messages) in to_check.iter().enumerate() {
+                let sign = messages.iter().fold(false, |s, &m| s ^ (m < 0.0));
+                // two smallest magnitudes, to exclude the own message
+                let (mut min1, mut min2, mut argmin) = (::std::f32::MAX, ::std::f32::MAX, 0);
+                for (i, &m) in messages.iter().enumerate() {
+                    let m = m.abs();
+                    if m < min1 {
+                        min2 = min1;
+                        min1 = m;
+                        argmin = i;
+                    } else if m < min2 {
+                        min2 = m;
+                    }
+                }
+                for (i, &m) in messages.iter().enumerate() {
+                    let magnitude = SCALE * if i == argmin { min2 } else { min1 };
+                    to_variable[c][i] = if sign ^ (m < 0.0) { -magnitude } else { magnitude };
+                }
+            }
+
+            for v in 0..matrix.n {
+                let checks = &matrix.variables[v];
+                let total: f32 = channel[v] + checks.iter()
+                    .zip(self.edges[v].iter())
+                    .map(|(&c, &i)| to_variable[c][i])
+                    .sum::<f32>();
+                word[v] = total < 0.0;
+                for (&c, &i) in checks.iter().zip(self.edges[v].iter()) {
+                    to_check[c][i] = total - to_variable[c][i];
+                }
+            }
+        }
+        self.max_iterations
+    }
+}
+
+impl Coding for Ldpc {
+    fn encode(&self, input: BitVec) -> BitVec {
+        let len = input.len();
+        let k = self.data();
+        let mut result = BitVec::with_capacity(len * self.block() / k + self.block());
+        let mut word = vec![false; self.block()];
+
+        for start in (0..len).step_by(k) {
+            let d = (len - start).min(k);
+            let bits: Vec<bool> = (start..start + d).map(|i| input.get(i).unwrap()).collect();
+
+            for b in word.iter_mut() {
+                *b = false;
+            }
+            for (i, &bit) in bits.iter().enumerate() {
+                word[self.data[i]] = bit;
+            }
+            for (&column, equation) in self.parity.iter().zip(self.equations.iter()) {
+                word[column] = equation.iter()
+                    .take_while(|&&i| i < d)
+                    .fold(false, |p, &i| p ^ bits[i]);
+            }
+
+            let sent = self.sent(d);
+            result.extend(word.iter().zip(sent.iter()).filter(|&(_, &s)| s).map(|(&b, _)| b));
+        }
+
+        result
+    }
+
+    fn decode(&self, input: BitVec) -> (BitVec, Stats) {
+        let mut stats = Stats::new();
+        let len = input.len();
+        let r = self.parity.len();
+        let mut result = BitVec::with_capacity(len);
+        let mut offset = 0;
+
+        while offset < len {
+            let end = len.min(offset + self.block());
+            if end - offset <= r {
+                // not even the parity is complete, nothing to decode
+                stats.detected += 1;
+                break;
+            }
+            let d = end - offset - r;
+            let sent = self.sent(d);
+
+            let mut word = vec![false; self.block()];
+            let mut received = (offset..end).map(|i| input.get(i).unwrap());
+            for (bit, &s) in word.iter_mut().zip(sent.iter()) {
+                if s {
+                    *bit = received.next().unwrap();
+                }
+            }
+
+            if self.matrix.syndrome_weight(&word) > 0 {
+                stats.detected += 1;
+                let iterations = match self.decoder {
+                    Decoder::BitFlipping => self.bit_flipping(&mut word, &sent),
+                    Decoder::MinSum => self.min_sum(&mut word, &sent),
+                };
+                let weight = self.matrix.syndrome_weight(&word);
+                if weight == 0 {
+                    stats.corrected += 1;
+                }
+                stats.iterations += iterations as u32;
+                stats.syndrome_weight += weight as u32;
+            }
+
+            result.extend(self.data[..d].iter().map(|&column| word[column]));
+            offset = end;
+        }
+
+        (result, stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use coding::message;
+
+    /// Hamming (7,4) as a tiny LDPC matrix.
+    const HAMMING: &str = "7 3\n3 4\n1 1 2 1 2 2 3\n4 4 4\n\
+                           1 0 0\n2 0 0\n1 2 0\n3 0 0\n1 3 0\n2 3 0\n1 2 3\n\
+                           1 3 5 7\n2 3 6 7\n4 5 6 7\n";
+
+    #[test]
+    fn alist() {
+        let matrix = ParityCheck::from_alist(HAMMING).unwrap();
+        assert_eq!(vec![vec![0, 2, 4, 6], vec![1, 2, 5, 6], vec![3, 4, 5, 6]], matrix.checks);
+        assert_eq!(matrix, ParityCheck::from_alist(&matrix.to_alist()).unwrap());
+
+        let regular = ParityCheck::regular(96, 3, 6, 1);
+        assert_eq!(regular, ParityCheck::from_alist(&regular.to_alist()).unwrap());
+
+        assert_eq!(Err(Error::ExpectedMoreData), ParityCheck::from_alist("7 3\n3 4\n1 1"));
+        assert_eq!(Err(Error::InvalidNumber("x".into())), ParityCheck::from_alist("7 x"));
+        let wrong = HAMMING.replace("4 5 6 7", "4 5 6 1");
+        assert_eq!(Err(Error::Inconsistent), ParityCheck::from_alist(&wrong));
+    }
+
+    #[test]
+    fn regular() {
+        let matrix = ParityCheck::regular(120, 3, 6, 7);
+        assert_eq!(60, matrix.m());
+        assert!(matrix.variables.iter().all(|checks| checks.len() == 3));
+        assert!(matrix.checks.iter().all(|variables| variables.len() == 6));
+        // same seed, same matrix
+        assert_eq!(matrix, ParityCheck::regular(120, 3, 6, 7));
+        assert!(matrix != ParityCheck::regular(120, 3, 6, 8));
+    }
+
+    #[test]
+    fn reverse() {
+        for &decoder in [Decoder::BitFlipping, Decoder::MinSum].iter() {
+            let hamming = Ldpc::new(ParityCheck::from_alist(HAMMING).unwrap(), decoder, 10);
+            assert_eq!(4, hamming.data());
+
+            // regular matrices are rank deficient
+            let ldpc = Ldpc::new(ParityCheck::regular(96, 3, 6, 1), decoder, 50);
+            assert!(ldpc.data() >= 48);
+
+            for code in [hamming, ldpc].iter() {
+                for &len in [0, 1, 4, 47, 100, 1000].iter() {
+                    let msg = message(len);
+                    let encoded = code.encode(msg.clone());
+                    let blocks = (len + code.data() - 1) / code.data();
+                    assert_eq!(len + (code.block() - code.data()) * blocks, encoded.len());
+
+                    let (decoded, stats) = code.decode(encoded);
+                    assert_eq!(msg, decoded);
+                    assert_eq!(0, stats.detected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn noise() {
+        let ldpc = Ldpc::new(ParityCheck::regular(816, 3, 6, 1), Decoder::MinSum, 50);
+        let msg = message(20 * ldpc.data());
+        let encoded = ldpc.encode(msg.clone());
+
+        // 1% of errors
+        let mut x: u32 = 7;
+        let noisy: BitVec = encoded.iter()
+            .map(|bit| {
+                x = x.wrapping_mul(1103515245).wrapping_add(12345);
+                bit ^ ((x >> 16) % 100 == 0)
+            })
+            .collect();
+
+        let (decoded, stats) = ldpc.decode(noisy.clone());
+        assert_eq!(msg, decoded);
+        assert!(stats.detected > 0);
+        assert_eq!(0, stats.uncorrectable());
+        assert_eq!(0, stats.syndrome_weight);
+        assert!(stats.iterations >= stats.detected);
+
+        let flipping = Ldpc { decoder: Decoder::BitFlipping, ..ldpc };
+        let (_, stats) = flipping.decode(noisy);
+        assert!(stats.corrected > 0);
+    }
+
+    #[test]
+    fn too_many_errors() {
+        let ldpc = Ldpc::new(ParityCheck::regular(96, 3, 6, 1), Decoder::MinSum, 20);
+        let encoded = ldpc.encode(message(ldpc.data()));
+        let noisy: BitVec = encoded.iter().enumerate().map(|(i, bit)| bit ^ (i % 3 == 0)).collect();
+
+        let (_, stats) = ldpc.decode(noisy);
+        assert_eq!((1, 0), (stats.detected, stats.corrected));
+        assert_eq!(20, stats.iterations);
+        assert!(stats.syndrome_weight > 0);
+    }
+}
@@ -1,20 +1,41 @@
+pub mod none;
 pub mod parity;
 pub mod repetition3;
 pub mod repetition5;
 pub mod hamming;
+pub mod extended_hamming;
+pub mod generic_hamming;
+pub mod reed_solomon;
+pub mod bch;
+pub mod convolutional;
+pub mod ldpc;
+pub mod interleaver;
+
+mod galois;
 
 use bit_vec::BitVec;
 
 
+/// Decoding report, in blocks (codewords) rather than bits, or in symbols for codes over bytes,
+/// or in trellis steps for convolutional codes.
 #[derive(Clone, Debug)]
 pub struct Stats {
     pub detected: u32,
     pub corrected: u32,
+    /// iterations spent by iterative decoders, zero for the rest
+    pub iterations: u32,
+    /// checks left unsatisfied by iterative decoders, zero for the rest
+    pub syndrome_weight: u32,
 }
 
 impl Stats {
     pub fn new() -> Stats {
-        Stats { detected: 0, corrected: 0 }
+        Stats { detected: 0, corrected: 0, iterations: 0, syndrome_weight: 0 }
+    }
+
+    /// Blocks with errors detected but left as is, so decoded data can not be trusted.
+    pub fn uncorrectable(&self) -> u32 {
+        self.detected.saturating_sub(self.corrected)
     }
 }
 
@@ -23,3 +44,56 @@ pub trait Coding {
     fn encode(&self, input: BitVec) -> BitVec;
     fn decode(&self, input: BitVec) -> (BitVec, Stats);
 }
+
+/// Decoding from channel reliabilities rather than hard bits.
+///
+/// Input is a log-likelihood ratio `ln(P(bit = 0) / P(bit = 1))` per received bit: its sign
+/// is the hard decision, and its magnitude is the confidence.  `Stats` count the same things
+/// as those of `Coding::decode`, taking the hard decisions as the received word.
+pub trait SoftDecoding: Coding {
+    fn decode_soft(&self, input: &[f64]) -> (BitVec, Stats);
+}
+
+/// Hard decisions of log-likelihood ratios.
+pub fn hard_decision(input: &[f64]) -> BitVec {
+    input.iter().map(|&llr| llr < 0.0).collect()
+}
+
+/// Pseudo random message of `len` bits, the same on every run.
+#[cfg(test)]
+fn message(len: usize) -> BitVec {
+    let mut x: u32 = 42;
+    (0..len)
+        .map(|_| {
+            x = x.wrapping_mul(1103515245).wrapping_add(12345);
+            (x >> 16) & 1 == 1
+        })
+        .collect()
+}
+
+/// Soft decoding of a repetition code: the sum of `n` ratios of a bit decides.
+fn decode_repetition_soft(input: &[f64], n: usize) -> (BitVec, Stats) {
+    let mut stats = Stats::new();
+    let result = input.chunks(n)
+        .filter(|chunk| chunk.len() == n)
+        .map(|chunk| {
+            let ones = chunk.iter().filter(|&&llr| llr < 0.0).count();
+            if ones != 0 && ones != n {
+                stats.detected += 1;
+                stats.corrected += 1;
+            }
+            chunk.iter().sum::<f64>() < 0.0
+        })
+        .collect();
+    (result, stats)
+}
+
+impl<C: Coding + ?Sized> Coding for Box<C> {
+    fn encode(&self, input: BitVec) -> BitVec {
+        (**self).encode(input)
+    }
+
+    fn decode(&self, input: BitVec) -> (BitVec, Stats) {
+        (**self).decode(input)
+    }
+}
@@ -0,0 +1,32 @@
+use super::{Coding, Stats};
+
+use bit_vec::BitVec;
+
+/// Identity code: no redundancy, so errors are neither detected nor corrected.
+#[derive(Debug)]
+pub struct NoCoding;
+
+impl Coding for NoCoding {
+    fn encode(&self, input: BitVec) -> BitVec {
+        input
+    }
+
+    fn decode(&self, input: BitVec) -> (BitVec, Stats) {
+        (input, Stats::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn identity() {
+        let input = BitVec::from_bytes(&[0b1010_0110]);
+        assert_eq!(input, NoCoding.encode(input.clone()));
+
+        let (decoded, stats) = NoCoding.decode(input.clone());
+        assert_eq!(input, decoded);
+        assert_eq!((0, 0), (stats.detected, stats.corrected));
+    }
+}
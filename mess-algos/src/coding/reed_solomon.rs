@@ -0,0 +1,251 @@
+//! Reed–Solomon (n, k) over GF(2^8).
+
+use super::{Coding, Stats};
+use super::galois::Field;
+use bit_vec::BitVec;
+
+/// Systematic Reed–Solomon code over GF(2^8), bytes are symbols.
+///
+/// Every `k` bytes of input are followed by `n - k` parity bytes, and up to `(n - k) / 2` wrong
+/// bytes per block are corrected, no matter how many bits of a byte are wrong.  So bursts of
+/// errors spanning a few bytes cost as much as a single flip.
+///
+/// On the wire a block is parity first, data last.  The last block may carry less than `k`
+/// bytes (shortened code), and its last byte may be incomplete: missing bits are zeros on both
+/// sides, so the output is exactly `8 * (n - k)` bits longer per block than the input.
+///
+/// `Stats` of the decoder are in symbols: every corrected byte is both detected and corrected,
+/// and a block with too many errors is counted as one detected byte.
+#[derive(Debug, Clone, Copy)]
+pub struct ReedSolomon {
+    pub n: usize,
+    pub k: usize,
+}
+
+impl ReedSolomon {
+    pub fn new(n: usize, k: usize) -> Self {
+        assert!(n <= 255, "n must not exceed 255");
+        assert!(k >= 1 && k < n, "k must be in range [1..n-1]");
+        ReedSolomon { n, k }
+    }
+
+    /// Parity bytes per block.
+    pub fn parity(&self) -> usize {
+        self.n - self.k
+    }
+
+    /// `(x - 2^0) (x - 2^1) … (x - 2^(n-k-1))`, highest degree first.
+    fn generator(&self, field: &Field) -> Vec<u8> {
+        let mut g = vec![1];
+        for i in 0..self.parity() {
+            let root = field.pow(i as isize);
+            let mut next = vec![0; g.len() + 1];
+            for (j, &c) in g.iter().enumerate() {
+                next[j] ^= c;
+                next[j + 1] ^= field.mul(c, root);
+            }
+            g = next;
+        }
+        g
+    }
+
+    /// Parity bytes for `data`: remainder of `data * x^(n-k)` divided by the generator.
+    fn encode_block(&self, field: &Field, generator: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut remainder = data.to_vec();
+        remainder.resize(data.len() + self.parity(), 0);
+        for i in 0..data.len() {
+            let c = remainder[i];
+            if c != 0 {
+                for (j, &g) in generator.iter().enumerate().skip(1) {
+                    remainder[i + j] ^= field.mul(g, c);
+                }
+            }
+        }
+        remainder.split_off(data.len())
+    }
+
+    /// Correct `codeword` (data followed by parity, highest degree first) in place.
+    ///
+    /// Returns the number of corrected bytes, or `None` if there are too many errors.
+    fn decode_block(&self, field: &Field, codeword: &mut [u8]) -> Option<usize> {
+        let nsym = self.parity();
+        let syndromes: Vec<u8> = (0..nsym).map(|i| field.eval(codeword, field.pow(i as isize))).collect();
+        if syndromes.iter().all(|&s| s == 0) {
+            return Some(0);
+        }
+
+        let locator = field.locator(&syndromes)?;
+        let positions = field.roots(&locator, codeword.len())?;
+        let errors = positions.len();
+        let len = codeword.len() as isize;
+
+        // Forney: magnitude is X Ω(X^-1) / Λ'(X^-1)
+        let mut evaluator = vec![0; nsym];
+        for (i, &s) in syndromes.iter().enumerate() {
+            for (j, &l) in locator.iter().enumerate().take(nsym - i) {
+                evaluator[i + j] ^= field.mul(s, l);
+            }
+        }
+        let derivative: Vec<u8> = locator.iter()
+            .enumerate()
+            .skip(1)
+            .map(|(j, &l)| if j % 2 == 1 { l } else { 0 })
+            .collect();
+
+        for &i in positions.iter() {
+            let degree = len - 1 - i as isize;
+            let x_inv = field.pow(-degree);
+            let denominator = field.eval_rev(&derivative, x_inv);
+            if denominator == 0 {
+                return None;
+            }
+            let magnitude = field.mul(field.pow(degree), field.div(field.eval_rev(&evaluator, x_inv), denominator));
+            codeword[i] ^= magnitude;
+        }
+
+        Some(errors)
+    }
+}
+
+/// Bytes of `input` in range of bits `[start, end)`, last byte padded with zeros.
+fn read_bytes(input: &BitVec, start: usize, end: usize) -> Vec<u8> {
+    let mut bytes = vec![0; (end - start + 7) / 8];
+    for i in start..end {
+        if input.get(i).unwrap() {
+            bytes[(i - start) / 8] |= 0x80 >> ((i - start) % 8);
+        }
+    }
+    bytes
+}
+
+fn write_bytes(output: &mut BitVec, bytes: &[u8], bits: usize) {
+    for i in 0..bits {
+        output.push(bytes[i / 8] & (0x80 >> (i % 8)) != 0);
+    }
+}
+
+impl Coding for ReedSolomon {
+    fn encode(&self, input: BitVec) -> BitVec {
+        let field = Field::new(8);
+        let generator = self.generator(&field);
+        let len = input.len();
+        let mut result = BitVec::with_capacity(len + (len / (8 * self.k) + 1) * 8 * self.parity());
+
+        for start in (0..len).step_by(8 * self.k) {
+            let end = len.min(start + 8 * self.k);
+            let data = read_bytes(&input, start, end);
+            let parity = self.encode_block(&field, &generator, &data);
+            write_bytes(&mut result, &parity, 8 * parity.len());
+            write_bytes(&mut result, &data, end - start);
+        }
+
+        result
+    }
+
+    fn decode(&self, input: BitVec) -> (BitVec, Stats) {
+        let field = Field::new(8);
+        let mut stats = Stats::new();
+        let len = input.len();
+        let parity_bits = 8 * self.parity();
+        let mut result = BitVec::with_capacity(len);
+        let mut offset = 0;
+
+        while offset < len {
+            let end = len.min(offset + 8 * self.n);
+            if end - offset <= parity_bits {
+                // not even the parity is complete, nothing to decode
+                stats.detected += 1;
+                break;
+            }
+            let data_bits = end - offset - parity_bits;
+
+            let mut codeword = read_bytes(&input, offset + parity_bits, end);
+            codeword.extend(read_bytes(&input, offset, offset + parity_bits));
+
+            match self.decode_block(&field, &mut codeword) {
+                Some(corrected) => {
+                    stats.detected += corrected as u32;
+                    stats.corrected += corrected as u32;
+                }
+                None => stats.detected += 1,
+            }
+            write_bytes(&mut result, &codeword, data_bits);
+            offset = end;
+        }
+
+        (result, stats)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use coding::message;
+
+    #[test]
+    fn reverse() {
+        for &(n, k) in [(255, 223), (15, 11), (7, 3), (3, 1)].iter() {
+            let rs = ReedSolomon::new(n, k);
+            for &len in [0, 1, 7, 8, 9, 88, 8 * k, 8 * k + 3, 3000].iter() {
+                let msg = message(len);
+                let code = rs.encode(msg.clone());
+                let blocks = (len + 8 * k - 1) / (8 * k);
+                assert_eq!(len + 8 * (n - k) * blocks, code.len());
+
+                let (decoded, stats) = rs.decode(code);
+                assert_eq!(msg, decoded);
+                assert_eq!(0, stats.detected);
+            }
+        }
+    }
+
+    #[test]
+    fn burst() {
+        let rs = ReedSolomon::new(255, 223);
+        let msg = message(8 * 1000 + 5);
+        let code = rs.encode(msg.clone());
+
+        // 16 whole bytes of the first block, and a 100 bits burst in the middle of the second
+        let mut noisy = code.clone();
+        for i in (0..16 * 8).chain(8 * 300..8 * 300 + 100) {
+            let bit = noisy[i];
+            noisy.set(i, !bit);
+        }
+        let (decoded, stats) = rs.decode(noisy);
+        assert_eq!(msg, decoded);
+        assert_eq!(16 + 13, stats.corrected);
+        assert_eq!(0, stats.uncorrectable());
+    }
+
+    #[test]
+    fn shortened_tail() {
+        let rs = ReedSolomon::new(15, 11);
+        let msg = message(8 * 11 + 13);
+        let code = rs.encode(msg.clone());
+
+        // last block has 2 data bytes, and 4 parity bytes, error in its parity and data
+        let tail = 8 * 15;
+        let mut noisy = code.clone();
+        for &i in [tail + 3, tail + 8 * 4 + 10].iter() {
+            let bit = noisy[i];
+            noisy.set(i, !bit);
+        }
+        let (decoded, stats) = rs.decode(noisy);
+        assert_eq!(msg, decoded);
+        assert_eq!((2, 2), (stats.detected, stats.corrected));
+    }
+
+    #[test]
+    fn too_many_errors() {
+        let rs = ReedSolomon::new(15, 11);
+        let msg = message(8 * 11);
+        let mut code = rs.encode(msg);
+        for i in 0..3 {
+            let bit = code[8 * 5 * i];
+            code.set(8 * 5 * i, !bit);
+        }
+        let (_, stats) = rs.decode(code);
+        assert_eq!(0, stats.corrected);
+        assert!(stats.uncorrectable() > 0);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Coding, Stats};
+use super::{Coding, SoftDecoding, Stats};
 
 use bit_vec::BitVec;
 
@@ -51,4 +51,10 @@ impl Coding for Repetition3 {
         }
         (result, stats)
     }
-}
\ No newline at end of file
+}
+
+impl SoftDecoding for Repetition3 {
+    fn decode_soft(&self, input: &[f64]) -> (BitVec, Stats) {
+        super::decode_repetition_soft(input, N)
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Coding, Stats};
+use super::{Coding, SoftDecoding, Stats};
 
 use bit_vec::BitVec;
 
@@ -55,10 +55,17 @@ impl Coding for Repetition5 {
     }
 }
 
+impl SoftDecoding for Repetition5 {
+    fn decode_soft(&self, input: &[f64]) -> (BitVec, Stats) {
+        super::decode_repetition_soft(input, N)
+    }
+}
+
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use coding::hard_decision;
 
     #[test]
     fn test() {
@@ -77,4 +84,13 @@ mod test {
         assert_eq!(1, stats.detected);
         assert_eq!(1, stats.corrected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn soft() {
+        // three weak flips outvote two confident bits in hard decision, but not in soft
+        let (decoded, stats) = Repetition5.decode_soft(&[2.0, -0.2, -0.2, 2.0, -0.2]);
+        assert_eq!(BitVec::from_elem(1, false), decoded);
+        assert_eq!((1, 1), (stats.detected, stats.corrected));
+        assert_eq!(BitVec::from_elem(1, true), Repetition5.decode(hard_decision(&[2.0, -0.2, -0.2, 2.0, -0.2])).0);
+    }
+}
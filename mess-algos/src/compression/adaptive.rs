@@ -0,0 +1,212 @@
+use bit_vec::BitVec;
+
+use super::{Compression, Error, read_bits, write_bits};
+
+/// Adaptive Huffman coding, FGK algorithm (Faller, Gallager, Knuth).
+///
+/// Both sides start with the same tree containing a single NYT ("not yet transmitted") node
+/// and update it after every symbol, so that the tree is always the Huffman tree for the
+/// symbols seen so far.  Neither a first pass over the input nor a code table is needed.
+///
+/// * Known symbol is encoded with its current code.
+/// * New symbol is encoded with the current code of NYT followed by 8 raw bits of the symbol.
+///
+/// Tree maintains the sibling property: nodes numbered in order of nondecreasing weight,
+/// siblings are adjacent.  Before incrementing a node's weight it is swapped with the
+/// highest-numbered node of the same weight (unless that is its parent).
+#[derive(Debug)]
+pub struct AdaptiveHuffman;
+
+/// 256 leaves + NYT + internal nodes.
+const MAX_NODES: usize = 2 * 257 - 1;
+const ROOT: usize = MAX_NODES - 1;
+
+#[derive(Copy, Clone, Default)]
+struct Node {
+    weight: u64,
+    parent: Option<usize>,
+    /// left (0) and right (1) children of internal node
+    children: Option<(usize, usize)>,
+    symbol: Option<u8>,
+}
+
+/// Nodes are stored at the indices equal to their numbers.
+struct Tree {
+    nodes: Vec<Node>,
+    leaves: [Option<usize>; 256],
+    nyt: usize,
+}
+
+impl Tree {
+    fn new() -> Self {
+        Tree {
+            nodes: vec![Node::default(); MAX_NODES],
+            leaves: [None; 256],
+            nyt: ROOT,
+        }
+    }
+
+    /// Code of the node: path from the root.
+    fn code(&self, mut node: usize) -> BitVec {
+        let mut path = Vec::new();
+        while let Some(parent) = self.nodes[node].parent {
+            let (_, right) = self.nodes[parent].children.unwrap();
+            path.push(node == right);
+            node = parent;
+        }
+        path.into_iter().rev().collect()
+    }
+
+    fn update(&mut self, symbol: u8) {
+        let mut node = match self.leaves[symbol as usize] {
+            Some(leaf) => leaf,
+            None => {
+                // NYT gives birth to new NYT and a leaf for the symbol
+                let parent = self.nyt;
+                let (nyt, leaf) = (parent - 2, parent - 1);
+                self.nodes[parent].children = Some((nyt, leaf));
+                self.nodes[nyt] = Node { parent: Some(parent), ..Node::default() };
+                self.nodes[leaf] = Node { parent: Some(parent), symbol: Some(symbol), ..Node::default() };
+                self.leaves[symbol as usize] = Some(leaf);
+                self.nyt = nyt;
+                leaf
+            }
+        };
+
+        loop {
+            let leader = self.leader(node);
+            if leader != node && Some(leader) != self.nodes[node].parent {
+                self.swap(node, leader);
+                node = leader;
+            }
+            self.nodes[node].weight += 1;
+            match self.nodes[node].parent {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+    }
+
+    /// Highest-numbered node with the same weight.
+    fn leader(&self, node: usize) -> usize {
+        let weight = self.nodes[node].weight;
+        let mut leader = node;
+        while leader < ROOT && self.nodes[leader + 1].weight == weight {
+            leader += 1;
+        }
+        leader
+    }
+
+    /// Swap subtrees rooted at `a` and `b`.  Nodes keep their numbers, i.e. positions, and parents.
+    fn swap(&mut self, a: usize, b: usize) {
+        let (parent_a, parent_b) = (self.nodes[a].parent, self.nodes[b].parent);
+        self.nodes.swap(a, b);
+        self.nodes[a].parent = parent_a;
+        self.nodes[b].parent = parent_b;
+
+        for &slot in [a, b].iter() {
+            let node = self.nodes[slot];
+            if let Some((left, right)) = node.children {
+                self.nodes[left].parent = Some(slot);
+                self.nodes[right].parent = Some(slot);
+            }
+            if let Some(symbol) = node.symbol {
+                self.leaves[symbol as usize] = Some(slot);
+            }
+        }
+        if self.nyt == a {
+            self.nyt = b;
+        } else if self.nyt == b {
+            self.nyt = a;
+        }
+    }
+}
+
+impl Compression<u8> for AdaptiveHuffman {
+    fn compress(&self, input: &[u8]) -> Result<BitVec, Error> {
+        let mut tree = Tree::new();
+        let mut output = BitVec::new();
+
+        for &symbol in input {
+            match tree.leaves[symbol as usize] {
+                Some(leaf) => output.extend(&tree.code(leaf)),
+                None => {
+                    output.extend(&tree.code(tree.nyt));
+                    write_bits(&mut output, symbol as usize, 8);
+                }
+            }
+            tree.update(symbol);
+        }
+
+        Ok(output)
+    }
+
+    fn decompress(&self, input: BitVec) -> Result<Vec<u8>, Error> {
+        let mut tree = Tree::new();
+        let mut output = Vec::new();
+        let mut offset = 0;
+
+        while offset < input.len() {
+            let mut node = ROOT;
+            while let Some((left, right)) = tree.nodes[node].children {
+                let bit = input.get(offset).ok_or(Error::ExpectedMoreData)?;
+                offset += 1;
+                node = if bit { right } else { left };
+            }
+
+            let symbol = match tree.nodes[node].symbol {
+                Some(symbol) => symbol,
+                None => read_bits(&input, &mut offset, 8)? as u8,
+            };
+            output.push(symbol);
+            tree.update(symbol);
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_compression(value: &[u8]) {
+        let compressed = AdaptiveHuffman.compress(value).unwrap();
+        assert_eq!(value, &*AdaptiveHuffman.decompress(compressed).unwrap());
+    }
+
+    #[test]
+    fn reverse() {
+        test_compression(b"");
+        test_compression(b"a");
+        test_compression(b"abracadabra");
+        test_compression(b"hello, world!");
+        test_compression(&(0..=255).chain(0..=255).collect::<Vec<u8>>());
+
+        let mut x: u32 = 42;
+        let random: Vec<u8> = (0..10000)
+            .map(|_| {
+                x = x.wrapping_mul(1103515245).wrapping_add(12345);
+                b"aaaaaaaabbbbccd"[(x >> 16) as usize % 15]
+            })
+            .collect();
+        test_compression(&random);
+    }
+
+    #[test]
+    fn adapts() {
+        // first symbol is sent raw, then the only known symbol costs 1 bit, while NYT costs 1 bit too
+        assert_eq!(8, AdaptiveHuffman.compress(b"a").unwrap().len());
+        assert_eq!(8 + 1 + 1 + 8, AdaptiveHuffman.compress(b"aab").unwrap().len());
+
+        let text = "a".repeat(1000);
+        assert!(AdaptiveHuffman.compress(text.as_bytes()).unwrap().len() < 8 + 1000);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut compressed = AdaptiveHuffman.compress(b"abc").unwrap();
+        compressed.pop();
+        assert_eq!(Err(Error::ExpectedMoreData), AdaptiveHuffman.decompress(compressed));
+    }
+}
@@ -0,0 +1,306 @@
+use bit_vec::BitVec;
+
+use super::{Compression, Error};
+
+/// Arithmetic coding with an adaptive order-0 model.
+///
+/// The whole message is represented by a single number in `[0, 1)`: every symbol narrows
+/// the current interval proportionally to its probability, so the output approaches the
+/// entropy of the source, without the "whole bits per symbol" limitation of prefix codes.
+///
+/// This is the classic integer implementation by Witten, Neal and Cleary: 32 bit interval
+/// bounds, bits are shifted out as soon as they are known, and underflow (interval
+/// straddling the middle) is tracked with a counter of pending opposite bits.
+///
+/// Model starts with all 256 bytes and an end-of-stream symbol equally probable, and adjusts
+/// frequencies after every symbol on both sides, so no table is transmitted.
+#[derive(Debug)]
+pub struct Arithmetic;
+
+const CODE_BITS: u32 = 32;
+const TOP: u64 = (1 << CODE_BITS) - 1;
+const FIRST_QUARTER: u64 = 1 << (CODE_BITS - 2);
+const HALF: u64 = 2 * FIRST_QUARTER;
+const THIRD_QUARTER: u64 = 3 * FIRST_QUARTER;
+
+/// End-of-stream symbol, which follows all 256 bytes.
+const EOF: usize = 256;
+const SYMBOLS: usize = 257;
+/// Frequencies are halved once their total exceeds this limit.  Must be less than
+/// `FIRST_QUARTER` to keep intervals distinguishable.
+const MAX_TOTAL: u64 = 1 << 16;
+const INCREMENT: u64 = 32;
+
+/// Adaptive order-0 model.
+///
+/// Cumulative frequencies are kept in a Fenwick tree, so that both coding a symbol and
+/// looking one up by count take `O(log SYMBOLS)` rather than a scan over the alphabet.
+struct Model {
+    frequency: [u64; SYMBOLS],
+    /// Fenwick tree over `frequency`, 1-based: `tree[i]` is the sum of `i & -i` frequencies
+    /// ending with `frequency[i - 1]`.
+    tree: [u64; SYMBOLS + 1],
+    total: u64,
+}
+
+/// Largest power of two not greater than `SYMBOLS`, first step of descent in `Model::find`.
+const TREE_STEP: usize = 256;
+
+impl Model {
+    fn new() -> Self {
+        let mut model = Model {
+            frequency: [1; SYMBOLS],
+            tree: [0; SYMBOLS + 1],
+            total: SYMBOLS as u64,
+        };
+        model.rebuild();
+        model
+    }
+
+    /// Build the tree from scratch in linear time.
+    fn rebuild(&mut self) {
+        self.tree = [0; SYMBOLS + 1];
+        for i in 1..=SYMBOLS {
+            self.tree[i] += self.frequency[i - 1];
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= SYMBOLS {
+                self.tree[parent] += self.tree[i];
+            }
+        }
+    }
+
+    /// Sum of frequencies of all symbols before `symbol`.
+    fn cumulative(&self, symbol: usize) -> u64 {
+        let mut sum = 0;
+        let mut i = symbol;
+        while i > 0 {
+            sum += self.tree[i];
+            i &= i - 1;
+        }
+        sum
+    }
+
+    /// Cumulative frequency range `[low, high)` of the symbol.
+    fn range(&self, symbol: usize) -> (u64, u64) {
+        let low = self.cumulative(symbol);
+        (low, low + self.frequency[symbol])
+    }
+
+    /// Symbol whose cumulative frequency range contains `count`, and the range.
+    fn find(&self, count: u64) -> (usize, u64, u64) {
+        debug_assert!(count < self.total);
+
+        // descend to the last position whose prefix sum does not exceed `count`
+        let mut symbol = 0;
+        let mut low = 0;
+        let mut step = TREE_STEP;
+        while step > 0 {
+            let next = symbol + step;
+            if next <= SYMBOLS && low + self.tree[next] <= count {
+                symbol = next;
+                low += self.tree[next];
+            }
+            step /= 2;
+        }
+        (symbol, low, low + self.frequency[symbol])
+    }
+
+    fn update(&mut self, symbol: usize) {
+        self.frequency[symbol] += INCREMENT;
+        self.total += INCREMENT;
+
+        let mut i = symbol + 1;
+        while i <= SYMBOLS {
+            self.tree[i] += INCREMENT;
+            i += i & i.wrapping_neg();
+        }
+
+        if self.total > MAX_TOTAL {
+            self.total = 0;
+            for f in self.frequency.iter_mut() {
+                *f = (*f + 1) / 2;
+                self.total += *f;
+            }
+            self.rebuild();
+        }
+    }
+}
+
+struct Encoder {
+    low: u64,
+    high: u64,
+    pending: usize,
+    output: BitVec,
+}
+
+impl Encoder {
+    fn encode(&mut self, (low, high): (u64, u64), total: u64) {
+        let range = self.high - self.low + 1;
+        self.high = self.low + range * high / total - 1;
+        self.low = self.low + range * low / total;
+
+        loop {
+            if self.high < HALF {
+                self.emit(false);
+            } else if self.low >= HALF {
+                self.emit(true);
+                self.low -= HALF;
+                self.high -= HALF;
+            } else if self.low >= FIRST_QUARTER && self.high < THIRD_QUARTER {
+                self.pending += 1;
+                self.low -= FIRST_QUARTER;
+                self.high -= FIRST_QUARTER;
+            } else {
+                break;
+            }
+            self.low = 2 * self.low;
+            self.high = 2 * self.high + 1;
+        }
+    }
+
+    fn emit(&mut self, bit: bool) {
+        self.output.push(bit);
+        for _ in 0..self.pending {
+            self.output.push(!bit);
+        }
+        self.pending = 0;
+    }
+
+    /// Output enough bits to pin a number inside the final interval.
+    fn finish(mut self) -> BitVec {
+        self.pending += 1;
+        let bit = self.low >= FIRST_QUARTER;
+        self.emit(bit);
+        self.output
+    }
+}
+
+impl Compression<u8> for Arithmetic {
+    fn compress(&self, input: &[u8]) -> Result<BitVec, Error> {
+        let mut model = Model::new();
+        let mut encoder = Encoder { low: 0, high: TOP, pending: 0, output: BitVec::new() };
+
+        for symbol in input.iter().map(|&b| b as usize).chain(Some(EOF)) {
+            encoder.encode(model.range(symbol), model.total);
+            model.update(symbol);
+        }
+
+        Ok(encoder.finish())
+    }
+
+    fn decompress(&self, input: BitVec) -> Result<Vec<u8>, Error> {
+        let mut model = Model::new();
+        let mut output = Vec::new();
+
+        // bits past the end of input are zeros
+        let mut offset = 0;
+        let next = |offset: &mut usize| {
+            let bit = input.get(*offset).unwrap_or(false);
+            *offset += 1;
+            bit as u64
+        };
+
+        let (mut low, mut high) = (0, TOP);
+        let mut value = 0;
+        for _ in 0..CODE_BITS {
+            value = 2 * value + next(&mut offset);
+        }
+
+        loop {
+            let range = high - low + 1;
+            let count = ((value - low + 1) * model.total - 1) / range;
+            let (symbol, sym_low, sym_high) = model.find(count);
+
+            if symbol == EOF {
+                break;
+            }
+            output.push(symbol as u8);
+
+            high = low + range * sym_high / model.total - 1;
+            low = low + range * sym_low / model.total;
+            model.update(symbol);
+
+            loop {
+                if high < HALF {
+                    // nothing
+                } else if low >= HALF {
+                    value -= HALF;
+                    low -= HALF;
+                    high -= HALF;
+                } else if low >= FIRST_QUARTER && high < THIRD_QUARTER {
+                    value -= FIRST_QUARTER;
+                    low -= FIRST_QUARTER;
+                    high -= FIRST_QUARTER;
+                } else {
+                    break;
+                }
+                low = 2 * low;
+                high = 2 * high + 1;
+                value = 2 * value + next(&mut offset);
+            }
+
+            // padding can not run longer than the register
+            if offset > input.len() + CODE_BITS as usize {
+                return Err(Error::ExpectedMoreData);
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_compression(value: &[u8]) -> usize {
+        let compressed = Arithmetic.compress(value).unwrap();
+        let len = compressed.len();
+        assert_eq!(value, &*Arithmetic.decompress(compressed).unwrap());
+        len
+    }
+
+    #[test]
+    fn reverse() {
+        test_compression(b"");
+        test_compression(b"a");
+        test_compression(b"abracadabra");
+        test_compression(&(0..=255).chain(0..=255).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn skewed() {
+        // entropy of this source is about 0.08 bits per symbol, way below 1 bit of any prefix code
+        let mut x: u32 = 42;
+        let skewed: Vec<u8> = (0..100_000)
+            .map(|_| {
+                x = x.wrapping_mul(1103515245).wrapping_add(12345);
+                if (x >> 16) % 100 == 0 { b'b' } else { b'a' }
+            })
+            .collect();
+        let len = test_compression(&skewed);
+        assert!(len < skewed.len() / 5, "{} bits", len);
+    }
+
+    #[test]
+    fn model() {
+        let mut model = Model::new();
+        for &symbol in [0, 7, 7, 255, EOF, 100].iter() {
+            model.update(symbol);
+        }
+        // also after frequencies are halved
+        for symbol in 0..3000 {
+            model.update(symbol % SYMBOLS);
+        }
+
+        let mut low = 0;
+        for symbol in 0..SYMBOLS {
+            let high = low + model.frequency[symbol];
+            assert_eq!((low, high), model.range(symbol));
+            assert_eq!((symbol, low, high), model.find(low));
+            assert_eq!((symbol, low, high), model.find(high - 1));
+            low = high;
+        }
+        assert_eq!(model.total, low);
+    }
+}
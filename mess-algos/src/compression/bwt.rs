@@ -0,0 +1,162 @@
+use bit_vec::BitVec;
+
+use super::{Compression, Error};
+
+/// Burrows–Wheeler transform.
+///
+/// Not a compression by itself: output is exactly 4 bytes per block longer than the input.
+/// But it groups together bytes followed by similar contexts, turning natural text into
+/// long runs, which makes it a great first stage for a `Pipeline` like bzip2's
+/// `Bwt → Mtf → Rle → CanonicalHuffman`.
+///
+/// Input is split into blocks of `block_size` bytes (the last one may be shorter).  For every
+/// block, all its cyclic rotations are sorted, and the last column of the sorted matrix is
+/// written after the row index of the original block as 4 bytes big-endian integer.
+#[derive(Debug, Clone, Copy)]
+pub struct Bwt {
+    pub block_size: usize,
+}
+
+impl Bwt {
+    pub fn new(block_size: usize) -> Self {
+        assert!(block_size >= 1 && block_size <= ::std::u32::MAX as usize, "block_size out of range");
+        Bwt { block_size }
+    }
+}
+
+impl Default for Bwt {
+    /// 100 KB blocks, same as `bzip2 -1`.
+    fn default() -> Self {
+        Bwt::new(100_000)
+    }
+}
+
+/// Start positions of the cyclic rotations of the `block`, in sorted order.
+///
+/// Prefix doubling: rotations are sorted by their first `k` bytes, then by `2 * k` bytes
+/// comparing pairs of ranks of `k`-prefixes, and so on until all ranks are distinct.
+fn sort_rotations(block: &[u8]) -> Vec<usize> {
+    let n = block.len();
+    let mut rotations: Vec<usize> = (0..n).collect();
+    let mut rank: Vec<usize> = block.iter().map(|&b| b as usize).collect();
+    let mut next_rank = vec![0; n];
+    let mut k = 1;
+
+    loop {
+        {
+            let key = |i: usize| (rank[i], rank[(i + k) % n]);
+            rotations.sort_by_key(|&i| key(i));
+
+            next_rank[rotations[0]] = 0;
+            for w in 1..n {
+                let (prev, cur) = (rotations[w - 1], rotations[w]);
+                next_rank[cur] = next_rank[prev] + (key(prev) != key(cur)) as usize;
+            }
+        }
+        ::std::mem::swap(&mut rank, &mut next_rank);
+
+        // either all rotations are distinct, or block is periodic and equal rotations are equal
+        if rank[rotations[n - 1]] == n - 1 || k >= n {
+            break;
+        }
+        k *= 2;
+    }
+
+    rotations
+}
+
+impl Compression<u8> for Bwt {
+    fn compress(&self, input: &[u8]) -> Result<BitVec, Error> {
+        let mut output = Vec::with_capacity(input.len() + 4 * (input.len() / self.block_size + 1));
+
+        for block in input.chunks(self.block_size) {
+            let n = block.len();
+            let rotations = sort_rotations(block);
+            let primary = rotations.iter().position(|&i| i == 0).unwrap() as u32;
+
+            output.extend(&[(primary >> 24) as u8, (primary >> 16) as u8, (primary >> 8) as u8, primary as u8]);
+            output.extend(rotations.iter().map(|&i| block[(i + n - 1) % n]));
+        }
+
+        Ok(BitVec::from_bytes(&output))
+    }
+
+    fn decompress(&self, input: BitVec) -> Result<Vec<u8>, Error> {
+        let input = input.to_bytes();
+        let mut output = Vec::with_capacity(input.len());
+
+        for chunk in input.chunks(4 + self.block_size) {
+            if chunk.len() <= 4 {
+                return Err(Error::ExpectedMoreData);
+            }
+            let primary = chunk[..4].iter().fold(0usize, |acc, &b| acc << 8 | b as usize);
+            let last = &chunk[4..];
+            let n = last.len();
+            if primary >= n {
+                return Err(Error::InvalidHeader);
+            }
+
+            // number of bytes less than given one, i.e. where its rows start in the first column
+            let mut less = [0usize; 256];
+            for &b in last {
+                less[b as usize] += 1;
+            }
+            let mut sum = 0;
+            for count in less.iter_mut() {
+                let c = *count;
+                *count = sum;
+                sum += c;
+            }
+
+            // LF mapping: row `i` rotated right by one byte is row `lf[i]`
+            let mut seen = [0usize; 256];
+            let lf: Vec<usize> = last
+                .iter()
+                .map(|&b| {
+                    let row = less[b as usize] + seen[b as usize];
+                    seen[b as usize] += 1;
+                    row
+                })
+                .collect();
+
+            let mut block = vec![0u8; n];
+            let mut row = primary;
+            for i in (0..n).rev() {
+                block[i] = last[row];
+                row = lf[row];
+            }
+            output.extend(block);
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_compression(bwt: Bwt, value: &[u8]) {
+        let transformed = bwt.compress(value).unwrap();
+        assert_eq!(value, &*bwt.decompress(transformed).unwrap());
+    }
+
+    #[test]
+    fn banana() {
+        let transformed = Bwt::default().compress(b"banana").unwrap().to_bytes();
+        assert_eq!(&[0, 0, 0, 3], &transformed[..4]);
+        assert_eq!(b"nnbaaa", &transformed[4..]);
+    }
+
+    #[test]
+    fn reverse() {
+        for &bwt in [Bwt::default(), Bwt::new(1), Bwt::new(7)].iter() {
+            test_compression(bwt, b"");
+            test_compression(bwt, b"a");
+            test_compression(bwt, b"aaaaaaaa");
+            test_compression(bwt, b"abababab");
+            test_compression(bwt, b"she sells sea shells by the sea shore");
+            test_compression(bwt, &(0..=255).rev().cycle().take(1000).collect::<Vec<u8>>());
+        }
+    }
+}
@@ -1,15 +1,17 @@
-use std::collections::HashMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
 use std::hash::Hash;
 
 use bit_vec::BitVec;
 
-use super::{Compression, Error};
+use super::{Compression, Error, read_bits, write_bits};
 
 #[derive(Clone)]
 pub struct Huffman<T> {
     pub events: HashMap<T, BitVec>,
     /// reverse map
     pub codes: HashMap<BitVec, T>,
+    decoder: Decoder<T>,
 }
 
 pub type Probability = f64;
@@ -22,7 +24,8 @@ impl<T: Eq + Hash + Clone> Huffman<T> {
                 (v.clone(), k.clone())
             })
             .collect();
-        Huffman { events, codes }
+        let decoder = Decoder::new(&events);
+        Huffman { events, codes, decoder }
     }
 
     pub fn with_probabilities<I>(events: I) -> Self
@@ -51,6 +54,11 @@ impl<T: Eq + Hash + Clone> Huffman<T> {
             total += 1;
         }
 
+        if total == 0 {
+            // nothing to encode, and probabilities of nothing do not sum up to 1
+            return Huffman::with_map(HashMap::new());
+        }
+
         Huffman::with_probabilities(
             frequency
                 .into_iter()
@@ -90,7 +98,12 @@ impl<T: Eq + Hash + Clone> Huffman<T> {
     }
 
     fn huffman_raw(p: HashMap<Vec<T>, Probability>) -> HashMap<Vec<T>, BitVec> {
-        if p.len() == 2 {
+        if p.len() == 1 {
+            // degenerate case: single event still needs a non-empty code
+            p.into_iter()
+             .map(|(k, _)| (k, BitVec::from_elem(1, false)))
+             .collect()
+        } else if p.len() == 2 {
             p.into_iter()
              .map(|(k, _)| k)
              .zip(vec![
@@ -144,6 +157,214 @@ impl<T: Eq + Hash + Clone> Huffman<T> {
     }
 }
 
+impl<T: Eq + Hash + Clone> Huffman<T> {
+    /// Naive decoder: probe `codes` map after every bit.
+    ///
+    /// Kept as a reference for benchmarks, `decompress` is much faster.
+    pub fn decompress_bitwise(&self, input: BitVec) -> Result<Vec<T>, Error> {
+        let mut offset: usize = 0;
+        let mut output: Vec<T> = Vec::new();
+
+        while offset < input.len() {
+            let mut slice = BitVec::new();
+
+            while let None = self.codes.get(&slice) {
+                match input.get(offset) {
+                    None => return Err(Error::ExpectedMoreData),
+                    Some(bit) => {
+                        slice.push(bit);
+                        offset += 1;
+                    }
+                }
+            }
+
+            let code = self.codes.get(&slice).unwrap();
+            output.push((*code).clone());
+        }
+
+        Ok(output)
+    }
+}
+
+/// Canonical Huffman codes.
+///
+/// Canonical code is fully described by the code length of every symbol: symbols are sorted
+/// by `(length, symbol)` and get consecutive binary numbers, shifted left whenever the length
+/// grows.  Thus it is enough to transmit lengths only, and the same input always produces
+/// the same code regardless of `HashMap` iteration order.
+impl<T: Eq + Hash + Clone + Ord> Huffman<T> {
+    /// Canonical code for the given code lengths.  Zero length means the symbol is absent.
+    ///
+    /// Fails with `Error::InvalidHeader` if lengths violate Kraft inequality.
+    pub fn with_lengths(lengths: HashMap<T, usize>) -> Result<Self, Error> {
+        let mut sorted: Vec<(usize, T)> = lengths
+            .into_iter()
+            .filter(|&(_, len)| len > 0)
+            .map(|(k, len)| (len, k))
+            .collect();
+        sorted.sort();
+
+        let mut events = HashMap::new();
+        let mut code: Option<BitVec> = None;
+
+        for (len, symbol) in sorted {
+            let mut next = match code {
+                None => BitVec::new(),
+                Some(mut prev) => {
+                    // binary increment; overflow means there is no room left in the code tree
+                    match prev.iter().rposition(|bit| !bit) {
+                        Some(zero) => {
+                            prev.set(zero, true);
+                            for i in zero + 1..prev.len() {
+                                prev.set(i, false);
+                            }
+                        }
+                        None => return Err(Error::InvalidHeader),
+                    }
+                    prev
+                }
+            };
+            while next.len() < len {
+                next.push(false);
+            }
+            events.insert(symbol, next.clone());
+            code = Some(next);
+        }
+
+        Ok(Huffman::new(events))
+    }
+
+    /// Optimal canonical code for the `input`.
+    ///
+    /// Unlike `optimal_for`, ties between equally frequent symbols are broken by symbol order,
+    /// so the result is deterministic.
+    pub fn canonical_for(input: &[T]) -> Self {
+        let mut frequency: BTreeMap<T, usize> = BTreeMap::new();
+        for i in input {
+            *frequency.entry(i.clone()).or_insert(0) += 1;
+        }
+
+        let symbols: Vec<T> = frequency.keys().cloned().collect();
+        // every node of the tree: leaves first, then merged nodes
+        let mut parent: Vec<usize> = vec![0; symbols.len()];
+        let mut heap: BinaryHeap<Reverse<(usize, usize)>> = frequency
+            .values()
+            .enumerate()
+            .map(|(id, &count)| Reverse((count, id)))
+            .collect();
+
+        while heap.len() >= 2 {
+            let Reverse((c1, n1)) = heap.pop().unwrap();
+            let Reverse((c2, n2)) = heap.pop().unwrap();
+            let id = parent.len();
+            parent.push(id);
+            parent[n1] = id;
+            parent[n2] = id;
+            heap.push(Reverse((c1 + c2, id)));
+        }
+
+        let lengths = symbols
+            .into_iter()
+            .enumerate()
+            .map(|(leaf, symbol)| {
+                let mut len = 0;
+                let mut node = leaf;
+                while parent[node] != node {
+                    node = parent[node];
+                    len += 1;
+                }
+                // single symbol still needs a non-empty code
+                (symbol, len.max(1))
+            })
+            .collect();
+
+        Huffman::with_lengths(lengths).unwrap()
+    }
+
+    /// Code length of every symbol.
+    pub fn lengths(&self) -> HashMap<T, usize> {
+        self.events
+            .iter()
+            .map(|(k, code)| (k.clone(), code.len()))
+            .collect()
+    }
+
+    /// Canonical code with the same code lengths.
+    pub fn canonical(&self) -> Self {
+        Huffman::with_lengths(self.lengths()).unwrap()
+    }
+}
+
+/// Bits to represent code length in a header.  Code lengths never exceed 255 for bytes.
+const HEADER_WIDTH_BITS: usize = 3;
+/// Bits to represent number of symbols in a sparse header, up to 256 inclusive.
+const HEADER_COUNT_BITS: usize = 9;
+
+impl Huffman<u8> {
+    /// Write code lengths to the `output`.
+    ///
+    /// Header only describes canonical codes, so `read_header` reproduces `self` only if it
+    /// is canonical.
+    ///
+    /// Layout:
+    ///
+    /// * `width - 1` of code lengths, 3 bits;
+    /// * sparse flag, 1 bit;
+    /// * sparse: number of symbols `n`, 9 bits, followed by `n` pairs `symbol:8 length:width`;
+    /// * dense: 256 lengths `length:width`, one for every byte, zero means absent.
+    pub fn write_header(&self, output: &mut BitVec) {
+        let max = self.events.values().map(|code| code.len()).max().unwrap_or(1);
+        let width = (0usize.leading_zeros() - max.leading_zeros()).max(1) as usize;
+
+        let n = self.events.len();
+        let sparse = n * (8 + width) + HEADER_COUNT_BITS < 256 * width;
+
+        write_bits(output, width - 1, HEADER_WIDTH_BITS);
+        output.push(sparse);
+
+        if sparse {
+            let mut symbols: Vec<&u8> = self.events.keys().collect();
+            symbols.sort();
+
+            write_bits(output, n, HEADER_COUNT_BITS);
+            for symbol in symbols {
+                write_bits(output, *symbol as usize, 8);
+                write_bits(output, self.events[symbol].len(), width);
+            }
+        } else {
+            for symbol in 0..256usize {
+                let len = self.events.get(&(symbol as u8)).map(|code| code.len()).unwrap_or(0);
+                write_bits(output, len, width);
+            }
+        }
+    }
+
+    /// Read canonical code written by `write_header`, starting at `offset`.
+    ///
+    /// `offset` is advanced past the header.
+    pub fn read_header(input: &BitVec, offset: &mut usize) -> Result<Self, Error> {
+        let width = read_bits(input, offset, HEADER_WIDTH_BITS)? + 1;
+        let sparse = read_bits(input, offset, 1)? == 1;
+
+        let mut lengths = HashMap::new();
+        if sparse {
+            let n = read_bits(input, offset, HEADER_COUNT_BITS)?;
+            for _ in 0..n {
+                let symbol = read_bits(input, offset, 8)? as u8;
+                let len = read_bits(input, offset, width)?;
+                lengths.insert(symbol, len);
+            }
+        } else {
+            for symbol in 0..256usize {
+                let len = read_bits(input, offset, width)?;
+                lengths.insert(symbol as u8, len);
+            }
+        }
+
+        Huffman::with_lengths(lengths)
+    }
+}
+
 impl<T> Compression<T> for Huffman<T>
     where T: Eq + Hash + Clone
 {
@@ -160,28 +381,177 @@ impl<T> Compression<T> for Huffman<T>
     }
 
     fn decompress(&self, input: BitVec) -> Result<Vec<T>, Error> {
-        let mut offset: usize = 0;
-        let mut output: Vec<T> = Vec::new();
+        self.decoder.decode(&input)
+    }
+}
 
-        while offset < input.len() {
-            let mut slice = BitVec::new();
+/// Maximum number of bits looked up at once by `Decoder`.
+const LOOKUP_BITS: usize = 10;
 
-            while let None = self.codes.get(&slice) {
-                match input.get(offset) {
-                    None => return Err(Error::ExpectedMoreData),
-                    Some(bit) => {
-                        slice.push(bit);
-                        offset += 1;
+/// Table-driven prefix code decoder.
+///
+/// Codes are arranged into a binary decoding tree.  On top of it, a lookup table indexed by
+/// the next `bits` bits of input either yields decoded symbol with its code length right away,
+/// or points to the tree node to continue walking from for longer codes.
+#[derive(Clone)]
+struct Decoder<T> {
+    symbols: Vec<T>,
+    tree: Vec<Node>,
+    table: Vec<Entry>,
+    bits: usize,
+}
+
+#[derive(Copy, Clone, Default)]
+struct Node {
+    children: [Option<usize>; 2],
+    /// index into `symbols`
+    leaf: Option<usize>,
+}
+
+#[derive(Copy, Clone)]
+enum Entry {
+    /// symbol index and code length
+    Leaf(usize, usize),
+    /// tree node reached after consuming all `bits` bits
+    Node(usize),
+    /// no code starts with these bits
+    Invalid,
+}
+
+impl<T: Clone> Decoder<T> {
+    fn new(events: &HashMap<T, BitVec>) -> Self {
+        let mut symbols = Vec::new();
+        let mut tree = vec![Node::default()];
+
+        for (symbol, code) in events.iter() {
+            // empty code can not be decoded without consuming input
+            if code.is_empty() {
+                continue;
+            }
+            let mut node = 0;
+            for bit in code.iter() {
+                node = match tree[node].children[bit as usize] {
+                    Some(child) => child,
+                    None => {
+                        tree.push(Node::default());
+                        let child = tree.len() - 1;
+                        tree[node].children[bit as usize] = Some(child);
+                        child
                     }
+                };
+            }
+            tree[node].leaf = Some(symbols.len());
+            symbols.push(symbol.clone());
+        }
+
+        let max = events.values().map(|code| code.len()).max().unwrap_or(0);
+        let bits = max.min(LOOKUP_BITS).max(1);
+        let mut table = vec![Entry::Invalid; 1 << bits];
+        Decoder::<T>::fill(&tree, &mut table, bits, 0, 0, 0);
+
+        Decoder { symbols, tree, table, bits }
+    }
+
+    /// Fill table entries for all inputs starting with `prefix` of `depth` bits leading to `node`.
+    fn fill(tree: &[Node], table: &mut [Entry], bits: usize, node: usize, prefix: usize, depth: usize) {
+        if let Some(leaf) = tree[node].leaf {
+            let free = bits - depth;
+            let start = prefix << free;
+            for entry in &mut table[start..start + (1 << free)] {
+                *entry = Entry::Leaf(leaf, depth);
+            }
+        } else if depth == bits {
+            table[prefix] = Entry::Node(node);
+        } else {
+            for bit in 0..2 {
+                if let Some(child) = tree[node].children[bit] {
+                    Decoder::<T>::fill(tree, table, bits, child, (prefix << 1) | bit, depth + 1);
                 }
             }
+        }
+    }
 
-            let code = self.codes.get(&slice).unwrap();
-            output.push((*code).clone());
+    fn decode(&self, input: &BitVec) -> Result<Vec<T>, Error> {
+        // `BitVec::to_bytes` goes bit by bit, while blocks keep bits least significant first.
+        let mut bytes = Vec::with_capacity(input.len() / 8 + 4);
+        for block in input.blocks() {
+            for i in 0..4 {
+                bytes.push(((block >> (8 * i)) as u8).reverse_bits());
+            }
+        }
+        let len = input.len();
+        let mut offset = 0;
+        let mut output = Vec::new();
+
+        while offset < len {
+            let node = if len - offset >= self.bits {
+                match self.table[peek(&bytes, offset, self.bits)] {
+                    Entry::Leaf(leaf, length) => {
+                        output.push(self.symbols[leaf].clone());
+                        offset += length;
+                        continue;
+                    }
+                    Entry::Node(node) => {
+                        offset += self.bits;
+                        node
+                    }
+                    Entry::Invalid => return Err(Error::ExpectedMoreData),
+                }
+            } else {
+                0
+            };
+            let leaf = self.walk(input, &mut offset, node)?;
+            output.push(self.symbols[leaf].clone());
         }
 
         Ok(output)
     }
+
+    /// Walk the tree bit by bit from the `node` down to a leaf.
+    fn walk(&self, input: &BitVec, offset: &mut usize, mut node: usize) -> Result<usize, Error> {
+        loop {
+            if let Some(leaf) = self.tree[node].leaf {
+                return Ok(leaf);
+            }
+            let bit = input.get(*offset).ok_or(Error::ExpectedMoreData)?;
+            *offset += 1;
+            node = self.tree[node].children[bit as usize].ok_or(Error::ExpectedMoreData)?;
+        }
+    }
+}
+
+/// Read `bits` (at most `LOOKUP_BITS`) bits at bit `offset` as an integer, most significant first.
+fn peek(bytes: &[u8], offset: usize, bits: usize) -> usize {
+    let start = offset / 8;
+    let mut window: usize = 0;
+    for i in 0..3 {
+        window = (window << 8) | *bytes.get(start + i).unwrap_or(&0) as usize;
+    }
+    (window >> (24 - offset % 8 - bits)) & ((1 << bits) - 1)
+}
+
+/// Self-describing canonical Huffman compression.
+///
+/// Compressed stream is a header (see `Huffman::write_header`) followed by the payload,
+/// encoded with the optimal canonical code for the input.  No side table is needed to
+/// decompress it, and the output is identical across runs and machines.
+#[derive(Debug)]
+pub struct CanonicalHuffman;
+
+impl Compression<u8> for CanonicalHuffman {
+    fn compress(&self, input: &[u8]) -> Result<BitVec, Error> {
+        let huffman = Huffman::canonical_for(input);
+        let mut output = BitVec::new();
+        huffman.write_header(&mut output);
+        output.extend(&huffman.compress(input)?);
+        Ok(output)
+    }
+
+    fn decompress(&self, input: BitVec) -> Result<Vec<u8>, Error> {
+        let mut offset = 0;
+        let huffman = Huffman::read_header(&input, &mut offset)?;
+        huffman.decompress(input.iter().skip(offset).collect())
+    }
 }
 
 #[cfg(test)]
@@ -232,4 +602,95 @@ mod test {
         assert!(vec.is_ok());
         assert_eq!(s.as_bytes(), &*h.decompress(vec.unwrap()).unwrap());
     }
+
+    #[test]
+    fn degenerate() {
+        let h: Huffman<u8> = Huffman::optimal_for(b"");
+        assert!(h.events.is_empty());
+        assert_eq!(0, h.compress(b"").unwrap().len());
+
+        let h: Huffman<u8> = Huffman::optimal_for(b"aaaa");
+        let vec = h.compress(b"aaaa").unwrap();
+        assert_eq!(4, vec.len());
+        assert_eq!(b"aaaa", &*h.decompress(vec).unwrap());
+    }
+
+    #[test]
+    fn canonical() {
+        let mut lengths = HashMap::new();
+        lengths.insert('A', 2);
+        lengths.insert('B', 1);
+        lengths.insert('C', 3);
+        lengths.insert('D', 3);
+        let h = Huffman::with_lengths(lengths).unwrap();
+
+        let code = |c| h.events[&c].iter().map(|b| if b { '1' } else { '0' }).collect::<String>();
+        assert_eq!("0", code('B'));
+        assert_eq!("10", code('A'));
+        assert_eq!("110", code('C'));
+        assert_eq!("111", code('D'));
+
+        let mut lengths = HashMap::new();
+        lengths.insert('A', 1);
+        lengths.insert('B', 1);
+        lengths.insert('C', 1);
+        assert_eq!(Err(Error::InvalidHeader), Huffman::with_lengths(lengths).map(|_| ()));
+    }
+
+    #[test]
+    fn canonical_optimal() {
+        let s = b"01110-1111";
+        let h: Huffman<u8> = Huffman::canonical_for(s);
+        assert_eq!(h.lengths(), Huffman::optimal_for(s).lengths());
+        assert_eq!(h.events, h.canonical().events);
+    }
+
+    #[test]
+    fn header() {
+        for s in [&b"hello, world!"[..], &(0..=255).collect::<Vec<u8>>()].iter() {
+            let h = Huffman::canonical_for(s);
+            let mut header = BitVec::new();
+            h.write_header(&mut header);
+            header.push(true);
+
+            let mut offset = 0;
+            let de = Huffman::read_header(&header, &mut offset).unwrap();
+            assert_eq!(header.len() - 1, offset);
+            assert_eq!(h.events, de.events);
+        }
+    }
+
+    #[test]
+    fn self_describing() {
+        let s = "she sells sea shells by the sea shore";
+        let vec = CanonicalHuffman.compress(s.as_bytes()).unwrap();
+        assert_eq!(vec, CanonicalHuffman.compress(s.as_bytes()).unwrap());
+        assert_eq!(s.as_bytes(), &*CanonicalHuffman.decompress(vec).unwrap());
+        assert_eq!(&[0u8; 0], &*CanonicalHuffman.decompress(CanonicalHuffman.compress(b"").unwrap()).unwrap());
+    }
+
+    #[test]
+    fn long_codes() {
+        // fibonacci frequencies produce codes longer than a lookup table covers
+        let mut s = Vec::new();
+        let (mut a, mut b) = (1, 1);
+        for symbol in 0..16u8 {
+            for _ in 0..a {
+                s.push(symbol);
+            }
+            let c = a + b;
+            a = b;
+            b = c;
+        }
+        let h: Huffman<u8> = Huffman::canonical_for(&s);
+        assert!(h.lengths().values().any(|&len| len > LOOKUP_BITS));
+
+        let vec = h.compress(&s).unwrap();
+        assert_eq!(s, h.decompress(vec.clone()).unwrap());
+        assert_eq!(s, h.decompress_bitwise(vec.clone()).unwrap());
+
+        let mut truncated = h.compress(&[0, 0]).unwrap();
+        truncated.pop();
+        assert_eq!(Err(Error::ExpectedMoreData), h.decompress(truncated));
+    }
 }
@@ -0,0 +1,216 @@
+use bit_vec::BitVec;
+
+use super::{Compression, Error, read_bits, write_bits};
+
+/// LZSS, the Storer–Szymanski flavour of LZ77 dictionary compression.
+///
+/// Repeated substrings are replaced with back references into a sliding window of recently
+/// seen input.  Output is byte-aligned, so that it can be further compressed by byte-oriented
+/// entropy coders, e.g. `CanonicalHuffman` in a `Pipeline`.
+///
+/// Tokens are grouped by 8, and every group is preceded by a flag byte, where bit `k` (most
+/// significant first) tells the kind of `k`-th token:
+///
+/// * `0`: literal byte;
+/// * `1`: reference `distance:window_bits length:length_bits`, padded with zeros to whole
+///   bytes, copies `length + min_match` bytes starting `distance + 1` bytes back.
+///   Source and destination may overlap.
+///
+/// References shorter than `min_match` would take at least as much space as literals,
+/// so they are never produced.
+#[derive(Debug, Clone, Copy)]
+pub struct Lzss {
+    /// sliding window size is `1 << window_bits` bytes
+    pub window_bits: usize,
+    /// look-ahead buffer, i.e. maximum match, is `(1 << length_bits) + min_match - 1` bytes
+    pub length_bits: usize,
+}
+
+/// Candidates to examine per position.  Limits worst case on highly repetitive input.
+const MAX_CHAIN: usize = 256;
+const NIL: usize = ::std::usize::MAX;
+
+impl Lzss {
+    pub fn new(window_bits: usize, length_bits: usize) -> Self {
+        assert!(window_bits >= 1 && window_bits <= 24, "window_bits must be in range [1..24]");
+        assert!(length_bits >= 1 && length_bits <= 16, "length_bits must be in range [1..16]");
+        Lzss { window_bits, length_bits }
+    }
+
+    /// Size of a reference in bytes.
+    pub fn reference_bytes(&self) -> usize {
+        (self.window_bits + self.length_bits + 7) / 8
+    }
+
+    /// Shortest match worth a reference.
+    pub fn min_match(&self) -> usize {
+        self.reference_bytes() + 1
+    }
+
+    pub fn window(&self) -> usize {
+        1 << self.window_bits
+    }
+
+    pub fn max_match(&self) -> usize {
+        (1 << self.length_bits) + self.min_match() - 1
+    }
+}
+
+impl Default for Lzss {
+    /// 4 KiB window and up to 18 bytes matches in 2 bytes references.
+    fn default() -> Self {
+        Lzss::new(12, 4)
+    }
+}
+
+impl Compression<u8> for Lzss {
+    fn compress(&self, input: &[u8]) -> Result<BitVec, Error> {
+        let (window, min_match, max_match) = (self.window(), self.min_match(), self.max_match());
+
+        // hash chains of positions keyed by two bytes starting at them
+        let mut head = vec![NIL; 1 << 16];
+        let mut prev = vec![NIL; input.len()];
+        let key = |i: usize| (input[i] as usize) << 8 | input[i + 1] as usize;
+
+        let padding = 8 * self.reference_bytes() - self.window_bits - self.length_bits;
+        let mut output = Vec::new();
+        let mut flags = 0;
+        let mut tokens = 0;
+        let mut i = 0;
+        let mut inserted = 0;
+
+        while i < input.len() {
+            let (mut best_len, mut best_dist) = (0, 0);
+
+            if i + 1 < input.len() {
+                let limit = max_match.min(input.len() - i);
+                let mut candidate = head[key(i)];
+                let mut chain = 0;
+
+                while candidate != NIL && i - candidate <= window && chain < MAX_CHAIN {
+                    let len = (0..limit)
+                        .take_while(|&k| input[candidate + k] == input[i + k])
+                        .count();
+                    if len > best_len {
+                        best_len = len;
+                        best_dist = i - candidate;
+                        if len == limit {
+                            break;
+                        }
+                    }
+                    candidate = prev[candidate];
+                    chain += 1;
+                }
+            }
+
+            if tokens % 8 == 0 {
+                flags = output.len();
+                output.push(0);
+            }
+            tokens += 1;
+
+            let step = if best_len >= min_match {
+                output[flags] |= 0x80 >> ((tokens - 1) % 8);
+
+                let mut reference = BitVec::new();
+                write_bits(&mut reference, best_dist - 1, self.window_bits);
+                write_bits(&mut reference, best_len - min_match, self.length_bits);
+                write_bits(&mut reference, 0, padding);
+                output.extend(reference.to_bytes());
+                best_len
+            } else {
+                output.push(input[i]);
+                1
+            };
+            i += step;
+
+            while inserted < i && inserted + 1 < input.len() {
+                let k = key(inserted);
+                prev[inserted] = head[k];
+                head[k] = inserted;
+                inserted += 1;
+            }
+        }
+
+        Ok(BitVec::from_bytes(&output))
+    }
+
+    fn decompress(&self, input: BitVec) -> Result<Vec<u8>, Error> {
+        let min_match = self.min_match();
+        let reference_bytes = self.reference_bytes();
+        let input = input.to_bytes();
+        let mut output = Vec::new();
+        let mut offset = 0;
+
+        while offset < input.len() {
+            let flags = input[offset];
+            offset += 1;
+
+            for k in 0..8 {
+                if offset == input.len() {
+                    break;
+                }
+                if flags & (0x80 >> k) == 0 {
+                    output.push(input[offset]);
+                    offset += 1;
+                    continue;
+                }
+                if offset + reference_bytes > input.len() {
+                    return Err(Error::ExpectedMoreData);
+                }
+                let reference = BitVec::from_bytes(&input[offset..offset + reference_bytes]);
+                offset += reference_bytes;
+
+                let mut bit = 0;
+                let distance = read_bits(&reference, &mut bit, self.window_bits)? + 1;
+                let length = read_bits(&reference, &mut bit, self.length_bits)? + min_match;
+                if distance > output.len() {
+                    return Err(Error::InvalidReference);
+                }
+                let start = output.len() - distance;
+                for i in 0..length {
+                    let byte = output[start + i];
+                    output.push(byte);
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_compression(lzss: Lzss, value: &[u8]) -> usize {
+        let compressed = lzss.compress(value).unwrap();
+        let len = compressed.len();
+        assert_eq!(value, &*lzss.decompress(compressed).unwrap());
+        len
+    }
+
+    #[test]
+    fn reverse() {
+        for &lzss in [Lzss::default(), Lzss::new(4, 2), Lzss::new(16, 8)].iter() {
+            test_compression(lzss, b"");
+            test_compression(lzss, b"a");
+            test_compression(lzss, b"abracadabra abracadabra");
+            test_compression(lzss, "to be or not to be, that is the question".repeat(20).as_bytes());
+            test_compression(lzss, &(0..=255).cycle().take(3000).collect::<Vec<u8>>());
+        }
+    }
+
+    #[test]
+    fn overlap() {
+        // run of the same byte is a reference to the previous byte, overlapping itself
+        let len = test_compression(Lzss::default(), &[b'a'; 19]);
+        assert_eq!(8 * (1 + 1 + 2), len);
+    }
+
+    #[test]
+    fn invalid_reference() {
+        let input = BitVec::from_bytes(&[0x80, 0, 0]);
+        assert_eq!(Err(Error::InvalidReference), Lzss::default().decompress(input));
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use bit_vec::BitVec;
+
+use super::{Compression, Error, read_bits, write_bits};
+
+/// Lempel–Ziv–Welch dictionary compression.
+///
+/// Dictionary initially holds all 256 single bytes.  Encoder emits the code of the longest
+/// known string, and adds that string followed by the next byte as a new entry.  Decoder
+/// rebuilds the same dictionary from the codes alone, one step behind, so no table is sent.
+///
+/// * Codes `0..256` are bytes, `256` is `CLEAR`, new entries start at `257`.
+/// * Codes are `9` bits wide initially, and grow by one bit as soon as the largest code
+///   that may come next stops fitting.
+/// * Once the dictionary reaches `1 << max_bits` entries, `CLEAR` is emitted and both sides
+///   start over with the initial dictionary.
+#[derive(Debug, Clone, Copy)]
+pub struct Lzw {
+    pub max_bits: usize,
+}
+
+const CLEAR: usize = 256;
+const FIRST: usize = 257;
+const MIN_BITS: usize = 9;
+
+impl Lzw {
+    pub fn new(max_bits: usize) -> Self {
+        assert!(max_bits >= MIN_BITS && max_bits <= 24, "max_bits must be in range [9..24]");
+        Lzw { max_bits }
+    }
+}
+
+impl Default for Lzw {
+    /// 4096 entries dictionary.
+    fn default() -> Self {
+        Lzw::new(12)
+    }
+}
+
+/// Width of codes when the largest possible code is `max_code`.
+fn width(max_code: usize) -> usize {
+    ((0usize.leading_zeros() - max_code.leading_zeros()) as usize).max(MIN_BITS)
+}
+
+impl Compression<u8> for Lzw {
+    fn compress(&self, input: &[u8]) -> Result<BitVec, Error> {
+        let mut dictionary: HashMap<(usize, u8), usize> = HashMap::new();
+        let mut next = FIRST;
+        let mut output = BitVec::new();
+        let mut current: Option<usize> = None;
+
+        for &byte in input {
+            current = match current {
+                None => Some(byte as usize),
+                Some(prefix) => {
+                    if let Some(&code) = dictionary.get(&(prefix, byte)) {
+                        Some(code)
+                    } else {
+                        write_bits(&mut output, prefix, width(next - 1));
+                        dictionary.insert((prefix, byte), next);
+                        next += 1;
+
+                        if next == 1 << self.max_bits {
+                            write_bits(&mut output, CLEAR, width(next - 1));
+                            dictionary.clear();
+                            next = FIRST;
+                        }
+                        Some(byte as usize)
+                    }
+                }
+            };
+        }
+        if let Some(prefix) = current {
+            write_bits(&mut output, prefix, width(next - 1));
+        }
+
+        Ok(output)
+    }
+
+    fn decompress(&self, input: BitVec) -> Result<Vec<u8>, Error> {
+        let initial: Vec<Vec<u8>> = (0..FIRST).map(|b| vec![b as u8]).collect();
+        let mut dictionary = initial.clone();
+        let mut output = Vec::new();
+        let mut previous: Option<Vec<u8>> = None;
+        let mut offset = 0;
+
+        while offset < input.len() {
+            // encoder is one entry ahead, and may refer to the entry decoder is about to add
+            let max_code = match previous {
+                None => dictionary.len() - 1,
+                Some(_) => dictionary.len(),
+            };
+            let code = read_bits(&input, &mut offset, width(max_code))?;
+
+            if code == CLEAR {
+                dictionary = initial.clone();
+                previous = None;
+                continue;
+            }
+
+            let entry = match previous {
+                None if code < CLEAR => dictionary[code].clone(),
+                Some(ref previous) if code < dictionary.len() => {
+                    let entry = dictionary[code].clone();
+                    let mut new = previous.clone();
+                    new.push(entry[0]);
+                    dictionary.push(new);
+                    entry
+                }
+                Some(ref previous) if code == dictionary.len() => {
+                    let mut entry = previous.clone();
+                    entry.push(previous[0]);
+                    dictionary.push(entry.clone());
+                    entry
+                }
+                _ => return Err(Error::InvalidReference),
+            };
+
+            output.extend(&entry);
+            previous = Some(entry);
+        }
+
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_compression(lzw: Lzw, value: &[u8]) -> usize {
+        let compressed = lzw.compress(value).unwrap();
+        let len = compressed.len();
+        assert_eq!(value, &*lzw.decompress(compressed).unwrap());
+        len
+    }
+
+    #[test]
+    fn reverse() {
+        let lzw = Lzw::default();
+        test_compression(lzw, b"");
+        test_compression(lzw, b"a");
+        test_compression(lzw, b"abracadabra abracadabra");
+        test_compression(lzw, &(0..=255).cycle().take(3000).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn tobeornot() {
+        // 24 bytes into 16 codes of 9 bits
+        assert_eq!(16 * 9, test_compression(Lzw::default(), b"TOBEORNOTTOBEORTOBEORNOT"));
+    }
+
+    #[test]
+    fn kwkwk() {
+        // code for "aa" is used right after it is defined, before decoder knows it
+        test_compression(Lzw::default(), b"aaaaaaaaaa");
+    }
+
+    #[test]
+    fn growing_and_reset() {
+        let mut x: u32 = 42;
+        let random: Vec<u8> = (0..20000)
+            .map(|_| {
+                x = x.wrapping_mul(1103515245).wrapping_add(12345);
+                b"abcdefgh"[(x >> 16) as usize % 8]
+            })
+            .collect();
+        // 9 bits dictionary is full almost at once
+        test_compression(Lzw::new(9), &random);
+        test_compression(Lzw::new(10), &random);
+        test_compression(Lzw::default(), &random);
+        test_compression(Lzw::new(16), &random);
+    }
+
+    #[test]
+    fn invalid_reference() {
+        let mut input = BitVec::new();
+        write_bits(&mut input, 300, 9);
+        assert_eq!(Err(Error::InvalidReference), Lzw::default().decompress(input));
+    }
+}
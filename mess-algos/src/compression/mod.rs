@@ -1,8 +1,16 @@
 //! Various compression methods.
 
+pub mod none;
 pub mod rle;
 pub mod huffman;
 pub mod shannon;
+pub mod adaptive;
+pub mod arithmetic;
+pub mod lzss;
+pub mod lzw;
+pub mod bwt;
+pub mod mtf;
+pub mod pipeline;
 
 use bit_vec::BitVec;
 
@@ -23,4 +31,30 @@ pub trait Compression<T> {
 pub enum Error {
     ExpectedMoreData,
     ZeroRepetition,
+    /// self-describing header can not be parsed or describes an impossible code.
+    InvalidHeader,
+    /// reference points outside of the data decoded so far.
+    InvalidReference,
+}
+
+/// Append `width` least significant bits of `value` to `output`, most significant first.
+pub fn write_bits(output: &mut BitVec, value: usize, width: usize) {
+    for i in (0..width).rev() {
+        output.push((value >> i) & 1 == 1);
+    }
+}
+
+/// Read `width` bits starting at `offset` as an unsigned integer, most significant first.
+///
+/// `offset` is advanced past the read bits.
+pub fn read_bits(input: &BitVec, offset: &mut usize, width: usize) -> Result<usize, Error> {
+    let mut value = 0;
+    for _ in 0..width {
+        match input.get(*offset) {
+            Some(bit) => value = (value << 1) | bit as usize,
+            None => return Err(Error::ExpectedMoreData),
+        }
+        *offset += 1;
+    }
+    Ok(value)
 }
\ No newline at end of file
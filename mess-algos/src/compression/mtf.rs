@@ -0,0 +1,64 @@
+use bit_vec::BitVec;
+
+use super::{Compression, Error};
+
+/// Move-to-front transform.
+///
+/// Every byte is replaced with its index in a list of all 256 bytes, and then moved to the
+/// front of that list.  Recently seen bytes get small indices, so after `Bwt` the output is
+/// dominated by zeros and ones, which suits `Rle` and entropy coders well.
+///
+/// Output has exactly the same size as the input.
+#[derive(Debug)]
+pub struct Mtf;
+
+fn alphabet() -> Vec<u8> {
+    (0..=255).collect()
+}
+
+impl Compression<u8> for Mtf {
+    fn compress(&self, input: &[u8]) -> Result<BitVec, Error> {
+        let mut list = alphabet();
+        let output: Vec<u8> = input
+            .iter()
+            .map(|&byte| {
+                let index = list.iter().position(|&b| b == byte).unwrap();
+                list.remove(index);
+                list.insert(0, byte);
+                index as u8
+            })
+            .collect();
+        Ok(BitVec::from_bytes(&output))
+    }
+
+    fn decompress(&self, input: BitVec) -> Result<Vec<u8>, Error> {
+        let mut list = alphabet();
+        let output = input
+            .to_bytes()
+            .into_iter()
+            .map(|index| {
+                let byte = list.remove(index as usize);
+                list.insert(0, byte);
+                byte
+            })
+            .collect();
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mtf() {
+        assert_eq!(&[98, 0, 98, 0, 1, 1], &*Mtf.compress(b"bbaaba").unwrap().to_bytes());
+        assert_eq!(b"bbaaba", &*Mtf.decompress(BitVec::from_bytes(&[98, 0, 98, 0, 1, 1])).unwrap());
+    }
+
+    #[test]
+    fn reverse() {
+        let s = b"she sells sea shells by the sea shore";
+        assert_eq!(&s[..], &*Mtf.decompress(Mtf.compress(s).unwrap()).unwrap());
+    }
+}
@@ -0,0 +1,35 @@
+use bit_vec::BitVec;
+
+use super::{Compression, Error};
+
+/// Identity compression: bytes are passed through as they are.
+///
+/// Baseline for comparisons, and the right choice for already compressed payloads (zip, jpeg),
+/// which other methods only expand.
+#[derive(Debug)]
+pub struct NoCompression;
+
+impl Compression<u8> for NoCompression {
+    fn compress(&self, input: &[u8]) -> Result<BitVec, Error> {
+        Ok(BitVec::from_bytes(input))
+    }
+
+    fn decompress(&self, input: BitVec) -> Result<Vec<u8>, Error> {
+        if input.len() % 8 != 0 {
+            return Err(Error::ExpectedMoreData);
+        }
+        Ok(input.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reverse() {
+        let compressed = NoCompression.compress(b"abc").unwrap();
+        assert_eq!(24, compressed.len());
+        assert_eq!(b"abc", &*NoCompression.decompress(compressed).unwrap());
+    }
+}
@@ -0,0 +1,115 @@
+use bit_vec::BitVec;
+
+use super::{Compression, Error};
+
+/// Sequence of compressions applied one after another, e.g. LZSS followed by Huffman
+/// in the spirit of DEFLATE.
+///
+/// Output of every stage but the last is fed into the next one as bytes.  Since
+/// compressed streams are not byte-aligned, such intermediate bytes are prefixed with
+/// the number of padding bits at the end.
+pub struct Pipeline {
+    pub stages: Vec<Box<Compression<u8>>>,
+}
+
+impl Pipeline {
+    pub fn new(stages: Vec<Box<Compression<u8>>>) -> Self {
+        Pipeline { stages }
+    }
+}
+
+/// Bits to bytes, prefixed with the amount of padding.
+pub fn pack(bits: BitVec) -> Vec<u8> {
+    let padding = (8 - bits.len() % 8) % 8;
+    let mut bytes = vec![padding as u8];
+    bytes.extend(bits.to_bytes());
+    bytes
+}
+
+/// Inverse of `pack`.
+pub fn unpack(bytes: &[u8]) -> Result<BitVec, Error> {
+    match bytes.split_first() {
+        Some((&padding, rest)) if padding < 8 && (padding == 0 || !rest.is_empty()) => {
+            let mut bits = BitVec::from_bytes(rest);
+            let len = bits.len() - padding as usize;
+            bits.truncate(len);
+            Ok(bits)
+        }
+        Some(_) => Err(Error::InvalidHeader),
+        None => Err(Error::ExpectedMoreData),
+    }
+}
+
+impl Compression<u8> for Pipeline {
+    fn compress(&self, input: &[u8]) -> Result<BitVec, Error> {
+        let mut bytes = input.to_vec();
+        let mut bits = BitVec::from_bytes(&bytes);
+
+        for (i, stage) in self.stages.iter().enumerate() {
+            bits = stage.compress(&bytes)?;
+            if i + 1 < self.stages.len() {
+                bytes = pack(bits.clone());
+            }
+        }
+
+        Ok(bits)
+    }
+
+    fn decompress(&self, input: BitVec) -> Result<Vec<u8>, Error> {
+        let mut bits = input;
+        let mut bytes = bits.to_bytes();
+
+        for (i, stage) in self.stages.iter().enumerate().rev() {
+            bytes = stage.decompress(bits)?;
+            if i > 0 {
+                bits = unpack(&bytes)?;
+            } else {
+                bits = BitVec::new();
+            }
+        }
+
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use compression::bwt::Bwt;
+    use compression::huffman::CanonicalHuffman;
+    use compression::lzss::Lzss;
+    use compression::mtf::Mtf;
+    use compression::rle::Rle;
+
+    #[test]
+    fn reverse() {
+        let text = "to be or not to be, that is the question. ".repeat(20);
+        let pipeline = Pipeline::new(vec![Box::new(Lzss::default()), Box::new(CanonicalHuffman)]);
+
+        let compressed = pipeline.compress(text.as_bytes()).unwrap();
+        assert!(compressed.len() < Lzss::default().compress(text.as_bytes()).unwrap().len());
+        assert_eq!(text.as_bytes(), &*pipeline.decompress(compressed).unwrap());
+
+        let empty = Pipeline::new(vec![]);
+        assert_eq!(b"abc", &*empty.decompress(empty.compress(b"abc").unwrap()).unwrap());
+
+        let rle = Pipeline::new(vec![Box::new(Rle), Box::new(Rle), Box::new(Rle)]);
+        assert_eq!(b"aaab", &*rle.decompress(rle.compress(b"aaab").unwrap()).unwrap());
+    }
+
+    #[test]
+    fn bzip2() {
+        let text = "she sells sea shells by the sea shore. ".repeat(10);
+        let pipeline = Pipeline::new(vec![
+            Box::new(Bwt::default()),
+            Box::new(Mtf),
+            Box::new(Rle),
+            Box::new(CanonicalHuffman),
+        ]);
+
+        let compressed = pipeline.compress(text.as_bytes()).unwrap();
+        assert!(compressed.len() < CanonicalHuffman.compress(text.as_bytes()).unwrap().len());
+        assert!(compressed.len() < Rle.compress(text.as_bytes()).unwrap().len());
+        assert_eq!(text.as_bytes(), &*pipeline.decompress(compressed).unwrap());
+    }
+}
@@ -21,8 +21,18 @@ impl<T> ShannonFano<T>
         pairs.sort_by(|&(_, p1), &(_, p2)| p1.partial_cmp(&p2).unwrap());
 
         add_bit(&mut output, &pairs, true);
+        if output.len() == 1 {
+            // degenerate case: single event still needs a non-empty code
+            for code in output.values_mut() {
+                code.push(false);
+            }
+        }
+        ShannonFano::with_map(output)
+    }
+
+    pub fn with_map(map: HashMap<T, BitVec>) -> Self {
         ShannonFano {
-            huffman: Huffman::with_map(output)
+            huffman: Huffman::with_map(map)
         }
     }
 
@@ -36,6 +46,10 @@ impl<T> ShannonFano<T>
             total += 1;
         }
 
+        if total == 0 {
+            return ShannonFano::with_map(HashMap::new());
+        }
+
         ShannonFano::with_probabilities(
             frequency
                 .into_iter()
@@ -139,4 +153,12 @@ mod test {
         assert!(vec.is_ok());
         assert_eq!(s.as_bytes(), &*h.decompress(vec.unwrap()).unwrap());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn degenerate() {
+        let h: ShannonFano<u8> = ShannonFano::optimal_for(b"aaaa");
+        let vec = h.compress(b"aaaa").unwrap();
+        assert_eq!(4, vec.len());
+        assert_eq!(b"aaaa", &*h.decompress(vec).unwrap());
+    }
+}
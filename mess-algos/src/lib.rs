@@ -14,12 +14,14 @@ extern crate reqwest;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[cfg_attr(test, macro_use)]
 extern crate serde_json;
 extern crate base64;
 
 
 pub mod coding;
 pub mod compression;
+pub mod checksum;
 
 pub use coding::{Coding, Stats};
 pub use compression::Compression;
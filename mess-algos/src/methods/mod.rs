@@ -3,9 +3,11 @@
 //! - `getOnline = Online`
 //! - `getUpdates username:string = Updates`
 //! - `sendFile = FileId`
-//! - `sendText from:Username to:Username payload:Data = Bool`
-//! - `uploadFile from:Username to:Username meta:FileMeta file_id:FileId payload:Data = Bool`
+//! - `sendText from:Username to:Username payload:Data id:flags.0?int = Bool`
+//! - `uploadFile from:Username to:Username meta:FileMeta file_id:FileId payload:Data id:flags.0?int = Bool`
 //! - `downloadFile file_id:FileId = DownloadedFile`
+//! - `setNoise token:string from:flags.0?Username to:Username noise:Noise seed:flags.1?int = Bool`
+//! - `requestRetransmit from:Username to:Username id:int = Bool`
 
 use std::str;
 use std::io::{self, Read};
@@ -27,6 +29,8 @@ pub mod send_file;
 pub mod send_text;
 pub mod upload_file;
 pub mod download_file;
+pub mod set_noise;
+pub mod request_retransmit;
 
 pub use self::login::Login;
 pub use self::get_online::GetOnline;
@@ -35,6 +39,8 @@ pub use self::send_file::SendFile;
 pub use self::send_text::SendText;
 pub use self::upload_file::UploadFile;
 pub use self::download_file::DownloadFile;
+pub use self::set_noise::SetNoise;
+pub use self::request_retransmit::RequestRetransmit;
 
 
 pub trait Method: Serialize + DeserializeOwned {
@@ -0,0 +1,54 @@
+//! - `requestRetransmit from:Username to:Username id:int = Bool`
+
+use super::Method;
+use ::types;
+
+/// Negative acknowledgement: `from` could not decode message `id` sent by `to`.
+///
+/// Server passes it on to `to` as `RetransmitUpdate`, and the sender decides whether to send
+/// the message once again.
+#[derive(Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct RequestRetransmit {
+    pub from: types::Username,
+    pub to: types::Username,
+    pub id: i64,
+}
+
+impl Method for RequestRetransmit {
+    type Answer = bool;
+
+    fn endpoint() -> &'static str {
+        "requestRetransmit"
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json;
+    use types::Update;
+
+    #[test]
+    fn serde() {
+        let method = RequestRetransmit { from: "frank".into(), to: "daniel".into(), id: 7 };
+        let str = serde_json::to_string(&method).unwrap();
+        assert_eq!(r#"{"from":"frank","to":"daniel","id":7}"#, str);
+
+        // not to be mistaken for other updates, nor other updates for it
+        match serde_json::from_str(&str).unwrap() {
+            Update::RetransmitUpdate { from, to, id } => assert_eq!(("frank", "daniel", 7), (&*from, &*to, id)),
+            other => panic!("{:?}", other),
+        }
+        let text = Update::TextUpdate {
+            from: "daniel".into(),
+            to: "frank".into(),
+            payload: types::Data::from_bytes(b"rust", types::Compression::None, types::Coding::R3).unwrap(),
+            id: Some(7),
+        };
+        match serde_json::from_str(&serde_json::to_string(&text).unwrap()).unwrap() {
+            Update::TextUpdate { id, .. } => assert_eq!(Some(7), id),
+            other => panic!("{:?}", other),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-//! - `sendText from:Username to:Username payload:Data = Bool`
+//! - `sendText from:Username to:Username payload:Data id:flags.0?int = Bool`
 
 use super::Method;
 use ::types;
@@ -9,6 +9,9 @@ pub struct SendText {
     pub from: types::Username,
     pub to: types::Username,
     pub payload: types::Data,
+    /// message id for retransmissions, see `RequestRetransmit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
 }
 
 impl Method for SendText {
@@ -0,0 +1,31 @@
+//! - `setNoise token:string from:flags.0?Username to:Username noise:Noise seed:flags.1?int = Bool`
+
+use super::Method;
+use ::types;
+
+/// Admin method: set noise of the channel to `to`, either from everyone, or only `from`.
+///
+/// `token` must match admin token the server is started with, otherwise nothing is changed.
+/// Servers without admin token refuse this method altogether.
+///
+/// Link noise overrides noise of the receiver.  With `seed`, noise of the link is reproducible:
+/// seeds of successive payloads are `seed`, `seed + 1` and so on.
+#[derive(Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct SetNoise {
+    pub token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<types::Username>,
+    pub to: types::Username,
+    pub noise: types::Noise,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl Method for SetNoise {
+    type Answer = bool;
+
+    fn endpoint() -> &'static str {
+        "setNoise"
+    }
+}
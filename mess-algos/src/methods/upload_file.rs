@@ -1,4 +1,4 @@
-//! - `uploadFile from:Username to:Username meta:FileMeta file_id:FileId payload:Data = Bool`
+//! - `uploadFile from:Username to:Username meta:FileMeta file_id:FileId payload:Data id:flags.0?int = Bool`
 use super::Method;
 use ::types;
 
@@ -10,6 +10,9 @@ pub struct UploadFile {
     pub meta: types::FileMeta,
     pub file_id: types::FileId,
     pub payload: types::Data,
+    /// message id for retransmissions, see `RequestRetransmit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
 }
 
 impl Method for UploadFile {
@@ -40,7 +43,8 @@ mod test {
                 b"rust",
                 types::Compression::Rle,
                 types::Coding::Parity,
-            ).unwrap()
+            ).unwrap(),
+            id: None,
         };
 
         let str = serde_json::to_string(&method).unwrap();
@@ -1,57 +1,100 @@
 use rand;
-use rand::Rng;
+use rand::{Rng, SeedableRng, XorShiftRng};
+use rand::distributions::{IndependentSample, Normal};
+use bit_vec::BitVec;
 
-#[derive(Copy, Clone, Debug)]
-pub enum NoiseLevel {
-    /// Clean noise-free channel
-    Clean,
-    /// 1% noise
-    Noise001,
-    /// 5% noise
-    Noise005,
-    /// 15% noise
-    Noise015,
-    /// 100% noise, flips all bits
-    Noise100,
+use std::fmt;
+
+/// Bit error rate: probability of every bit to be flipped, in range `[0..1]`.
+///
+/// `1.0` flips all bits, while `0.5` destroys all information.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+pub struct NoiseLevel(f64);
+
+/// Mantissas of noise levels `increase` and `decrease` step through, in every decade.
+const STEPS: [f64; 6] = [1.0, 1.5, 2.0, 3.0, 5.0, 7.0];
+/// Lowest nonzero noise level reachable by `increase`.
+const MIN_STEP_EXPONENT: i32 = -4;
+/// Highest noise level reachable by `increase`: beyond it noise starts carrying information.
+const MAX_STEP: f64 = 0.5;
+
+/// Random number generator for reproducible noise: the same seed makes the same noise.
+pub fn seeded(seed: u64) -> XorShiftRng {
+    XorShiftRng::from_seed([seed as u32, (seed >> 32) as u32, 0x5851_f42d, 0x4c95_7f2d])
 }
 
 impl NoiseLevel {
+    /// Clean noise-free channel
+    pub const CLEAN: NoiseLevel = NoiseLevel(0.0);
+
+    pub fn new(p: f64) -> Self {
+        assert!(p >= 0.0 && p <= 1.0, "noise level must be in range [0..1]");
+        NoiseLevel(p)
+    }
+
+    /// All levels `increase` and `decrease` step through: `1`, `1.5`, `2`, `3`, `5` and `7`
+    /// times powers of ten from `0.0001` up to `0.5`, after zero.
+    pub fn steps() -> Vec<NoiseLevel> {
+        let mut steps = vec![NoiseLevel::CLEAN];
+        for exponent in MIN_STEP_EXPONENT..0 {
+            // division keeps values shortest in decimal, like `0.15`
+            let scale = 10f64.powi(-exponent);
+            steps.extend(STEPS.iter()
+                .map(|&mantissa| mantissa / scale)
+                .filter(|&p| p <= MAX_STEP)
+                .map(NoiseLevel));
+        }
+        steps
+    }
+
+    /// Next step up, see `steps`.  Levels above the last step are left as is.
+    pub fn increase(self) -> Self {
+        NoiseLevel::steps().into_iter().find(|step| step.0 > self.0 * (1.0 + 1e-9)).unwrap_or(self)
+    }
+
+    /// Next step down, see `steps`.
+    pub fn decrease(self) -> Self {
+        NoiseLevel::steps().into_iter().rev().find(|step| step.0 < self.0 * (1.0 - 1e-9)).unwrap_or(self)
+    }
+
     pub fn apply<I: Iterator>(self, iter: I) -> NoiseIter<I> {
-        NoiseIter::<I> {
+        self.apply_with(iter, rand::thread_rng())
+    }
+
+    /// Same as `apply`, drawing randomness from `rng`.
+    pub fn apply_with<I: Iterator, R: Rng>(self, iter: I, rng: R) -> NoiseIter<I, R> {
+        NoiseIter {
             it: iter,
             level: self.to_f64(),
-            rng: rand::thread_rng(),
+            rng,
         }
     }
 
     pub fn to_f64(self) -> f64 {
-        match self {
-            NoiseLevel::Clean => 0.0,
-            NoiseLevel::Noise001 => 0.01,
-            NoiseLevel::Noise005 => 0.05,
-            NoiseLevel::Noise015 => 0.15,
-            NoiseLevel::Noise100 => 1.00,
-        }
+        self.0
     }
-    pub fn to_str(self) -> &'static str {
-        match self {
-            NoiseLevel::Clean => "0.00",
-            NoiseLevel::Noise001 => "0.01",
-            NoiseLevel::Noise005 => "0.05",
-            NoiseLevel::Noise015 => "0.15",
-            NoiseLevel::Noise100 => "1.00",
-        }
+}
+
+impl fmt::Display for NoiseLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
 
-pub struct NoiseIter<I> {
+impl Channel for NoiseLevel {
+    fn transmit_with(&self, input: &BitVec, rng: &mut Rng) -> BitVec {
+        Bsc::new(self.to_f64()).transmit_with(input, rng)
+    }
+}
+
+pub struct NoiseIter<I, R = rand::ThreadRng> {
     it: I,
     level: f64,
-    rng: rand::ThreadRng,
+    rng: R,
 }
 
 
-impl<I: Iterator> Iterator for NoiseIter<I>
+impl<I: Iterator, R: Rng> Iterator for NoiseIter<I, R>
     where I::Item: ::std::ops::Not<Output=I::Item>
 {
     type Item = I::Item;
@@ -67,6 +110,224 @@ impl<I: Iterator> Iterator for NoiseIter<I>
     }
 }
 
+/// Model of a noisy channel: what is received when `input` is sent.
+pub trait Channel {
+    /// Received bits, drawing randomness from `rng`.  Length may differ from the input's.
+    fn transmit_with(&self, input: &BitVec, rng: &mut Rng) -> BitVec;
+
+    fn transmit(&self, input: &BitVec) -> BitVec {
+        self.transmit_with(input, &mut rand::thread_rng())
+    }
+
+    /// Reproducible transmission, see `seeded`.
+    fn transmit_seeded(&self, input: &BitVec, seed: u64) -> BitVec {
+        self.transmit_with(input, &mut seeded(seed))
+    }
+}
+
+/// Binary symmetric channel: every bit is flipped with probability `p`, independently.
+#[derive(Copy, Clone, Debug)]
+pub struct Bsc {
+    pub p: f64,
+}
+
+impl Bsc {
+    pub fn new(p: f64) -> Self {
+        assert!(p >= 0.0 && p <= 1.0, "p must be in range [0..1]");
+        Bsc { p }
+    }
+}
+
+impl Channel for Bsc {
+    fn transmit_with(&self, input: &BitVec, rng: &mut Rng) -> BitVec {
+        input.iter().map(|bit| bit ^ (rng.next_f64() < self.p)).collect()
+    }
+}
+
+/// Gilbert–Elliott channel: binary symmetric one, switching between good and bad states.
+///
+/// Errors come in bursts while the channel is in the bad state.  Every transmission starts in
+/// the good state.
+#[derive(Copy, Clone, Debug)]
+pub struct GilbertElliott {
+    /// probability of switching from the good state to the bad one after a bit
+    pub good_to_bad: f64,
+    /// probability of switching from the bad state to the good one after a bit
+    pub bad_to_good: f64,
+    /// flip probability in the good state
+    pub error_good: f64,
+    /// flip probability in the bad state
+    pub error_bad: f64,
+}
+
+impl GilbertElliott {
+    pub fn new(good_to_bad: f64, bad_to_good: f64, error_good: f64, error_bad: f64) -> Self {
+        for &p in [good_to_bad, bad_to_good, error_good, error_bad].iter() {
+            assert!(p >= 0.0 && p <= 1.0, "probabilities must be in range [0..1]");
+        }
+        assert!(bad_to_good > 0.0, "bad state must not be absorbing");
+        GilbertElliott { good_to_bad, bad_to_good, error_good, error_bad }
+    }
+
+    /// Error-free good state and coin flipping bad state (Gilbert model), tuned for the given
+    /// mean bit error rate below `0.5`, and mean length of bad state runs.
+    pub fn bursts(error_rate: f64, burst_length: f64) -> Self {
+        assert!(error_rate >= 0.0 && error_rate < 0.5, "error rate must be in range [0..0.5)");
+        assert!(burst_length >= 1.0, "burst length must be at least 1");
+        let bad_to_good = 1.0 / burst_length;
+        // bad state takes 2 * error_rate of the time
+        let bad = 2.0 * error_rate;
+        GilbertElliott::new(bad_to_good * bad / (1.0 - bad), bad_to_good, 0.0, 0.5)
+    }
+
+    /// Fraction of time spent in the bad state in the long run.
+    pub fn bad_fraction(&self) -> f64 {
+        self.good_to_bad / (self.good_to_bad + self.bad_to_good)
+    }
+
+    /// Bit error rate in the long run.
+    pub fn error_rate(&self) -> f64 {
+        let bad = self.bad_fraction();
+        (1.0 - bad) * self.error_good + bad * self.error_bad
+    }
+}
+
+impl Channel for GilbertElliott {
+    fn transmit_with(&self, input: &BitVec, rng: &mut Rng) -> BitVec {
+        let mut bad = false;
+        input.iter()
+            .map(|bit| {
+                let error = if bad { self.error_bad } else { self.error_good };
+                let received = bit ^ (rng.next_f64() < error);
+                let switch = if bad { self.bad_to_good } else { self.good_to_bad };
+                if rng.next_f64() < switch {
+                    bad = !bad;
+                }
+                received
+            })
+            .collect()
+    }
+}
+
+/// Binary erasure channel: every bit is lost with probability `p`, independently.
+///
+/// The receiver knows which bits are lost.  Hard-decision receivers, which see `BitVec`s from
+/// `Channel::transmit`, have to guess them, so half of the erasures end up as errors.
+#[derive(Copy, Clone, Debug)]
+pub struct Erasure {
+    pub p: f64,
+}
+
+impl Erasure {
+    pub fn new(p: f64) -> Self {
+        assert!(p >= 0.0 && p <= 1.0, "p must be in range [0..1]");
+        Erasure { p }
+    }
+
+    /// Received bits, `None` for erased ones.
+    pub fn erase(&self, input: &BitVec, rng: &mut Rng) -> Vec<Option<bool>> {
+        input.iter().map(|bit| if rng.next_f64() < self.p { None } else { Some(bit) }).collect()
+    }
+}
+
+impl Channel for Erasure {
+    fn transmit_with(&self, input: &BitVec, rng: &mut Rng) -> BitVec {
+        self.erase(input, rng)
+            .into_iter()
+            .map(|bit| bit.unwrap_or_else(|| rng.next_u32() & 1 == 1))
+            .collect()
+    }
+}
+
+/// Channel which loses synchronization: before every bit a random bit is inserted with
+/// probability `insertion`, and the bit itself is dropped with probability `deletion`.
+#[derive(Copy, Clone, Debug)]
+pub struct InsertionDeletion {
+    pub insertion: f64,
+    pub deletion: f64,
+}
+
+impl InsertionDeletion {
+    pub fn new(insertion: f64, deletion: f64) -> Self {
+        assert!(insertion >= 0.0 && insertion < 1.0, "insertion must be in range [0..1)");
+        assert!(deletion >= 0.0 && deletion <= 1.0, "deletion must be in range [0..1]");
+        InsertionDeletion { insertion, deletion }
+    }
+}
+
+impl Channel for InsertionDeletion {
+    fn transmit_with(&self, input: &BitVec, rng: &mut Rng) -> BitVec {
+        let mut result = BitVec::with_capacity(input.len());
+        for bit in input.iter() {
+            if rng.next_f64() < self.insertion {
+                result.push(rng.next_u32() & 1 == 1);
+            }
+            if rng.next_f64() >= self.deletion {
+                result.push(bit);
+            }
+        }
+        result
+    }
+}
+
+/// Additive white gaussian noise over BPSK: `0` is sent as `+1`, `1` as `-1`.
+///
+/// Unlike `NoiseLevel` it does not flip bits, but yields log-likelihood ratios of the received
+/// values for `coding::SoftDecoding`, or hard bits after `coding::hard_decision`.
+#[derive(Copy, Clone, Debug)]
+pub struct Awgn {
+    /// Eb/N0 per channel bit, in decibels
+    pub snr_db: f64,
+}
+
+impl Awgn {
+    pub fn new(snr_db: f64) -> Self {
+        Awgn { snr_db }
+    }
+
+    /// Standard deviation of the noise for unit energy per bit.
+    pub fn sigma(&self) -> f64 {
+        (0.5 / 10f64.powf(self.snr_db / 10.0)).sqrt()
+    }
+
+    /// Probability of a wrong hard decision.
+    pub fn bit_error_rate(&self) -> f64 {
+        let snr = 10f64.powf(self.snr_db / 10.0);
+        0.5 * erfc(snr.sqrt())
+    }
+
+    /// Log-likelihood ratios `2y / sigma^2` of every received bit.
+    pub fn apply<I: Iterator<Item = bool>>(&self, iter: I) -> Vec<f64> {
+        self.apply_with(iter, &mut rand::thread_rng())
+    }
+
+    /// Same as `apply`, drawing randomness from `rng`.
+    pub fn apply_with<I: Iterator<Item = bool>>(&self, iter: I, rng: &mut Rng) -> Vec<f64> {
+        let sigma = self.sigma();
+        let normal = Normal::new(0.0, sigma);
+        iter.map(|bit| {
+                let y = if bit { -1.0 } else { 1.0 } + normal.ind_sample(&mut &mut *rng);
+                2.0 * y / (sigma * sigma)
+            })
+            .collect()
+    }
+}
+
+impl Channel for Awgn {
+    /// Hard decisions of the received values.
+    fn transmit_with(&self, input: &BitVec, rng: &mut Rng) -> BitVec {
+        ::coding::hard_decision(&self.apply_with(input.iter(), rng))
+    }
+}
+
+/// Complementary error function, Abramowitz and Stegun 7.1.26 (error below 1.5e-7).
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erfc = poly * (-x * x).exp();
+    if x < 0.0 { 2.0 - erfc } else { erfc }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -76,11 +337,135 @@ mod test {
     fn flip_all() {
         let good = BitVec::from_elem(42, true);
         let bad = BitVec::from_elem(42, false);
-        assert_eq!(bad, NoiseLevel::Noise100.apply(good.iter()).collect::<BitVec>());
+        assert_eq!(bad, NoiseLevel::new(1.0).apply(good.iter()).collect::<BitVec>());
+        assert_eq!(good, NoiseLevel::CLEAN.apply(good.iter()).collect::<BitVec>());
+
+        println!("{:?}", NoiseLevel::new(0.01).apply(good.iter()).collect::<BitVec>());
+        println!("{:?}", NoiseLevel::new(0.05).apply(good.iter()).collect::<BitVec>());
+        println!("{:?}", NoiseLevel::new(0.15).apply(good.iter()).collect::<BitVec>());
+        println!("{:?}", NoiseLevel::new(1.0).apply(good.iter()).collect::<BitVec>());
+    }
 
-        println!("{:?}", NoiseLevel::Noise001.apply(good.iter()).collect::<BitVec>());
-        println!("{:?}", NoiseLevel::Noise005.apply(good.iter()).collect::<BitVec>());
-        println!("{:?}", NoiseLevel::Noise015.apply(good.iter()).collect::<BitVec>());
-        println!("{:?}", NoiseLevel::Noise100.apply(good.iter()).collect::<BitVec>());
+    #[test]
+    fn steps() {
+        let steps = NoiseLevel::steps();
+        assert_eq!(NoiseLevel::CLEAN, steps[0]);
+        assert_eq!(NoiseLevel::new(0.0001), steps[1]);
+        assert_eq!(NoiseLevel::new(0.5), *steps.last().unwrap());
+        assert!(steps.windows(2).all(|pair| pair[0] < pair[1]));
+        let names: Vec<String> = steps.iter().map(|step| step.to_string()).collect();
+        for name in ["0", "0.01", "0.05", "0.15", "0.0007", "0.3"].iter() {
+            assert!(names.contains(&name.to_string()), "{}", name);
+        }
+
+        assert_eq!(NoiseLevel::new(0.0001), NoiseLevel::CLEAN.increase());
+        assert_eq!(NoiseLevel::CLEAN, NoiseLevel::new(0.0001).decrease());
+        assert_eq!(NoiseLevel::CLEAN, NoiseLevel::CLEAN.decrease());
+        assert_eq!(NoiseLevel::new(0.2), NoiseLevel::new(0.15).increase());
+        assert_eq!(NoiseLevel::new(0.1), NoiseLevel::new(0.15).decrease());
+        assert_eq!(NoiseLevel::new(0.15), NoiseLevel::new(0.12).increase());
+        assert_eq!(NoiseLevel::new(0.1), NoiseLevel::new(0.12).decrease());
+        assert_eq!(NoiseLevel::new(0.5), NoiseLevel::new(0.5).increase());
+        assert_eq!(NoiseLevel::new(0.5), NoiseLevel::new(1.0).decrease());
+        assert_eq!(NoiseLevel::new(1.0), NoiseLevel::new(1.0).increase());
+    }
+
+    #[test]
+    fn awgn() {
+        let awgn = Awgn::new(4.0);
+        let good = BitVec::from_elem(100000, true);
+        let llr = awgn.apply_with(good.iter(), &mut seeded(1));
+        assert_eq!(good.len(), llr.len());
+
+        let errors = llr.iter().filter(|&&llr| llr > 0.0).count() as f64 / good.len() as f64;
+        let expected = awgn.bit_error_rate();
+        assert!((errors - expected).abs() < 0.003, "{} vs {}", errors, expected);
+        assert!((expected - 0.0125).abs() < 0.0005);
+    }
+
+    fn errors(lhs: &BitVec, rhs: &BitVec) -> usize {
+        lhs.iter().zip(rhs.iter()).filter(|&(l, r)| l != r).count()
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn bsc() {
+        let good = BitVec::from_elem(100000, false);
+        assert_eq!(good, Bsc::new(0.0).transmit_seeded(&good, 1));
+        assert_eq!(BitVec::from_elem(100000, true), Bsc::new(1.0).transmit_seeded(&good, 1));
+
+        let rate = errors(&good, &Bsc::new(0.05).transmit_seeded(&good, 1)) as f64 / good.len() as f64;
+        assert!((rate - 0.05).abs() < 0.005, "{}", rate);
+        assert_eq!(good.len(), NoiseLevel::new(0.15).transmit_seeded(&good, 1).len());
+    }
+
+    #[test]
+    fn gilbert_elliott() {
+        let channel = GilbertElliott::bursts(0.05, 10.0);
+        assert!((channel.error_rate() - 0.05).abs() < 1e-9);
+        assert!((channel.bad_fraction() - 0.1).abs() < 1e-9);
+
+        let good = BitVec::from_elem(200000, false);
+        let bad = channel.transmit_seeded(&good, 1);
+        assert_eq!(good.len(), bad.len());
+        let rate = errors(&good, &bad) as f64 / good.len() as f64;
+        assert!((rate - 0.05).abs() < 0.01, "{}", rate);
+
+        // bursty: errors come next to each other much more often than in BSC of the same rate
+        let pairs = bad.iter().zip(bad.iter().skip(1)).filter(|&(a, b)| a && b).count() as f64;
+        assert!(pairs / good.len() as f64 > 4.0 * rate * rate, "{}", pairs);
+    }
+
+    #[test]
+    fn erasure() {
+        let good = BitVec::from_elem(100000, true);
+        let channel = Erasure::new(0.2);
+        let received = channel.erase(&good, &mut seeded(1));
+        assert_eq!(good.len(), received.len());
+        assert!(received.iter().all(|&bit| bit != Some(false)));
+        let erased = received.iter().filter(|bit| bit.is_none()).count() as f64 / good.len() as f64;
+        assert!((erased - 0.2).abs() < 0.01, "{}", erased);
+
+        // half of the erasures are guessed right
+        let rate = errors(&good, &channel.transmit_seeded(&good, 1)) as f64 / good.len() as f64;
+        assert!((rate - 0.1).abs() < 0.01, "{}", rate);
+    }
+
+    #[test]
+    fn insertion_deletion() {
+        let good = BitVec::from_elem(100000, true);
+        assert_eq!(0, InsertionDeletion::new(0.0, 1.0).transmit_seeded(&good, 1).len());
+        assert_eq!(good, InsertionDeletion::new(0.0, 0.0).transmit_seeded(&good, 1));
+
+        let longer = InsertionDeletion::new(0.1, 0.0).transmit_seeded(&good, 1);
+        let ratio = longer.len() as f64 / good.len() as f64;
+        assert!((ratio - 1.1).abs() < 0.01, "{}", ratio);
+
+        let shorter = InsertionDeletion::new(0.0, 0.1).transmit_seeded(&good, 1);
+        assert!(shorter.all());
+        let ratio = shorter.len() as f64 / good.len() as f64;
+        assert!((ratio - 0.9).abs() < 0.01, "{}", ratio);
+    }
+
+    #[test]
+    fn reproducible() {
+        let good = BitVec::from_elem(1000, true);
+        let noisy = |seed| NoiseLevel::new(0.15).apply_with(good.iter(), seeded(seed)).collect::<BitVec>();
+        assert_eq!(noisy(42), noisy(42));
+        assert!(noisy(42) != noisy(43));
+        assert_eq!(noisy(0), noisy(0));
+
+        let channels: Vec<Box<Channel>> = vec![
+            Box::new(Bsc::new(0.1)),
+            Box::new(GilbertElliott::bursts(0.1, 8.0)),
+            Box::new(Erasure::new(0.1)),
+            Box::new(InsertionDeletion::new(0.05, 0.05)),
+            Box::new(Awgn::new(2.0)),
+        ];
+        for channel in channels.iter() {
+            assert_eq!(channel.transmit_seeded(&good, 7), channel.transmit_seeded(&good, 7));
+            assert!(channel.transmit_seeded(&good, 7) != channel.transmit_seeded(&good, 8));
+        }
+        assert_eq!(Awgn::new(2.0).apply_with(good.iter(), &mut seeded(1)),
+                   Awgn::new(2.0).apply_with(good.iter(), &mut seeded(1)));
+    }
+}
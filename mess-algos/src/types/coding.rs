@@ -1,23 +1,190 @@
 //! - `Coding`
+//!     * `None = Coding`
 //!     * `Hamming = Coding`
+//!     * `Secded = Coding`, extended Hamming (8,4)
+//!     * `GenericHamming r:int = Coding`, Hamming (2^r - 1, 2^r - r - 1), `r` in `[2..16]`
+//!     * `ReedSolomon n:int k:int = Coding`, `1 <= k < n <= 255`
+//!     * `Bch m:int t:int = Coding`, binary BCH of length `2^m - 1` correcting `t` errors
+//!     * `Convolutional constraint:int generators:(int, int) = Coding`, rate 1/2, Viterbi decoding
+//!     * `Interleaved coding:Coding interleaver:Interleaver = Coding`
 //!     * `Parity = Coding`
 //!     * `R3 = Coding`
 //!     * `R5 = Coding`
+//!
+//! - `Interleaver`
+//!     * `Block rows:int columns:int = Interleaver`
+//!     * `Convolutional branches:int delay:int = Interleaver`
+
+use serde::{Deserialize, Deserializer, de};
 
 #[derive(Clone, Debug)]
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Coding {
+    None,
     Hamming,
+    Secded,
+    #[serde(deserialize_with = "reed_solomon")]
+    ReedSolomon { n: u8, k: u8 },
+    #[serde(deserialize_with = "bch")]
+    Bch { m: u8, t: u8 },
+    #[serde(deserialize_with = "convolutional")]
+    Convolutional { constraint: u8, generators: (u32, u32) },
+    Interleaved { coding: Box<Coding>, interleaver: Interleaver },
+    GenericHamming {
+        #[serde(deserialize_with = "hamming_r")]
+        r: u8,
+    },
     Parity,
     R3,
     R5,
 }
 
+#[derive(Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Interleaver {
+    #[serde(deserialize_with = "block")]
+    Block { rows: u16, columns: u16 },
+    #[serde(deserialize_with = "convolutional_interleaver")]
+    Convolutional { branches: u16, delay: u16 },
+}
+
+/// Reject empty matrix.
+fn block<'de, D>(deserializer: D) -> Result<(u16, u16), D::Error>
+    where D: Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    struct Block {
+        rows: u16,
+        columns: u16,
+    }
+
+    let Block { rows, columns } = Block::deserialize(deserializer)?;
+    if rows == 0 || columns == 0 {
+        return Err(de::Error::custom("interleaver matrix must not be empty"));
+    }
+    Ok((rows, columns))
+}
+
+/// Reject zero branches.
+fn convolutional_interleaver<'de, D>(deserializer: D) -> Result<(u16, u16), D::Error>
+    where D: Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    struct Convolutional {
+        branches: u16,
+        delay: u16,
+    }
+
+    let Convolutional { branches, delay } = Convolutional::deserialize(deserializer)?;
+    if branches == 0 {
+        return Err(de::Error::custom("interleaver must have at least one branch"));
+    }
+    Ok((branches, delay))
+}
+
+impl Into<::coding::interleaver::Interleaver> for Interleaver {
+    fn into(self) -> ::coding::interleaver::Interleaver {
+        use ::coding::interleaver::Interleaver as I;
+
+        match self {
+            Interleaver::Block { rows, columns } => I::Block { rows: rows as usize, columns: columns as usize },
+            Interleaver::Convolutional { branches, delay } => {
+                I::Convolutional { branches: branches as usize, delay: delay as usize }
+            }
+        }
+    }
+}
+
+/// Reject `r` which `GenericHamming::new` would panic on.
+fn hamming_r<'de, D>(deserializer: D) -> Result<u8, D::Error>
+    where D: Deserializer<'de>
+{
+    let r = u8::deserialize(deserializer)?;
+    if r < 2 || r > 16 {
+        return Err(de::Error::custom(format!("hamming r must be in range [2..16], got {}", r)));
+    }
+    Ok(r)
+}
+
+/// Reject `n` and `k` which `ReedSolomon::new` would panic on.
+fn reed_solomon<'de, D>(deserializer: D) -> Result<(u8, u8), D::Error>
+    where D: Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    struct ReedSolomon {
+        n: u8,
+        k: u8,
+    }
+
+    let ReedSolomon { n, k } = ReedSolomon::deserialize(deserializer)?;
+    if k < 1 || k >= n {
+        return Err(de::Error::custom(format!("reed-solomon k must be in range [1..n-1], got n = {}, k = {}", n, k)));
+    }
+    Ok((n, k))
+}
+
+/// Reject `m` and `t` which `Bch::new` would panic on.
+fn bch<'de, D>(deserializer: D) -> Result<(u8, u8), D::Error>
+    where D: Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    struct Bch {
+        m: u8,
+        t: u8,
+    }
+
+    let Bch { m, t } = Bch::deserialize(deserializer)?;
+    if m < 3 || m > 8 {
+        return Err(de::Error::custom(format!("bch m must be in range [3..8], got {}", m)));
+    }
+    if t < 1 || 2 * t as usize >= (1 << m) - 1 {
+        return Err(de::Error::custom(format!("bch t must be in range [1..(2^m - 2) / 2], got {}", t)));
+    }
+    Ok((m, t))
+}
+
+/// Reject `constraint` and `generators` which `Convolutional::new` would panic on.
+fn convolutional<'de, D>(deserializer: D) -> Result<(u8, (u32, u32)), D::Error>
+    where D: Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    struct Convolutional {
+        constraint: u8,
+        generators: (u32, u32),
+    }
+
+    let Convolutional { constraint, generators } = Convolutional::deserialize(deserializer)?;
+    if constraint < 2 || constraint > 9 {
+        return Err(de::Error::custom(format!("constraint must be in range [2..9], got {}", constraint)));
+    }
+    for &g in [generators.0, generators.1].iter() {
+        if g == 0 || g >= 1 << constraint {
+            return Err(de::Error::custom(format!("generator must be a nonzero {} bit mask, got {}", constraint, g)));
+        }
+    }
+    Ok((constraint, generators))
+}
+
 impl Into<Box<::coding::Coding>> for Coding {
     fn into(self) -> Box<::coding::Coding> {
         match self {
+            Coding::None => Box::new(::coding::none::NoCoding),
             Coding::Hamming => Box::new(::coding::hamming::Hamming),
+            Coding::Secded => Box::new(::coding::extended_hamming::ExtendedHamming),
+            Coding::GenericHamming { r } => Box::new(::coding::generic_hamming::GenericHamming::new(r as usize)),
+            Coding::ReedSolomon { n, k } => {
+                Box::new(::coding::reed_solomon::ReedSolomon::new(n as usize, k as usize))
+            }
+            Coding::Bch { m, t } => Box::new(::coding::bch::Bch::new(m as usize, t as usize)),
+            Coding::Convolutional { constraint, generators } => {
+                Box::new(::coding::convolutional::Convolutional::new(constraint as usize, generators))
+            }
+            Coding::Interleaved { coding, interleaver } => {
+                let coding: Box<::coding::Coding> = (*coding).into();
+                Box::new(::coding::interleaver::Interleaved::new(coding, interleaver.into()))
+            }
             Coding::Parity => Box::new(::coding::parity::Parity),
             Coding::R3 => Box::new(::coding::repetition3::Repetition3),
             Coding::R5 => Box::new(::coding::repetition5::Repetition5),
@@ -25,12 +192,48 @@ impl Into<Box<::coding::Coding>> for Coding {
     }
 }
 
+impl From<::coding::none::NoCoding> for Coding {
+    fn from(_: ::coding::none::NoCoding) -> Self {
+        Coding::None
+    }
+}
+
 impl From<::coding::hamming::Hamming> for Coding {
     fn from(_: ::coding::hamming::Hamming) -> Self {
         Coding::Hamming
     }
 }
 
+impl From<::coding::extended_hamming::ExtendedHamming> for Coding {
+    fn from(_: ::coding::extended_hamming::ExtendedHamming) -> Self {
+        Coding::Secded
+    }
+}
+
+impl From<::coding::generic_hamming::GenericHamming> for Coding {
+    fn from(hamming: ::coding::generic_hamming::GenericHamming) -> Self {
+        Coding::GenericHamming { r: hamming.r as u8 }
+    }
+}
+
+impl From<::coding::reed_solomon::ReedSolomon> for Coding {
+    fn from(rs: ::coding::reed_solomon::ReedSolomon) -> Self {
+        Coding::ReedSolomon { n: rs.n as u8, k: rs.k as u8 }
+    }
+}
+
+impl From<::coding::bch::Bch> for Coding {
+    fn from(bch: ::coding::bch::Bch) -> Self {
+        Coding::Bch { m: bch.m as u8, t: bch.t as u8 }
+    }
+}
+
+impl From<::coding::convolutional::Convolutional> for Coding {
+    fn from(code: ::coding::convolutional::Convolutional) -> Self {
+        Coding::Convolutional { constraint: code.constraint as u8, generators: code.generators }
+    }
+}
+
 impl From<::coding::parity::Parity> for Coding {
     fn from(_: ::coding::parity::Parity) -> Self {
         Coding::Parity
@@ -68,4 +271,80 @@ mod test {
             _ => unreachable!(),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn generic_hamming() {
+        let json = serde_json::to_string(&Coding::GenericHamming { r: 4 }).unwrap();
+        assert_eq!(r#"{"generichamming":{"r":4}}"#, json);
+
+        match serde_json::from_str(&json).unwrap() {
+            Coding::GenericHamming { r } => assert_eq!(4, r),
+            _ => unreachable!(),
+        }
+        assert!(serde_json::from_str::<Coding>(r#"{"generichamming":{"r":1}}"#).is_err());
+        assert!(serde_json::from_str::<Coding>(r#"{"generichamming":{"r":17}}"#).is_err());
+    }
+
+    #[test]
+    fn reed_solomon() {
+        let json = serde_json::to_string(&Coding::ReedSolomon { n: 255, k: 223 }).unwrap();
+        assert_eq!(r#"{"reedsolomon":{"n":255,"k":223}}"#, json);
+
+        match serde_json::from_str(&json).unwrap() {
+            Coding::ReedSolomon { n, k } => assert_eq!((255, 223), (n, k)),
+            _ => unreachable!(),
+        }
+        assert!(serde_json::from_str::<Coding>(r#"{"reedsolomon":{"n":10,"k":10}}"#).is_err());
+        assert!(serde_json::from_str::<Coding>(r#"{"reedsolomon":{"n":10,"k":0}}"#).is_err());
+    }
+
+    #[test]
+    fn bch() {
+        let json = serde_json::to_string(&Coding::Bch { m: 4, t: 2 }).unwrap();
+        assert_eq!(r#"{"bch":{"m":4,"t":2}}"#, json);
+
+        match serde_json::from_str(&json).unwrap() {
+            Coding::Bch { m, t } => assert_eq!((4, 2), (m, t)),
+            _ => unreachable!(),
+        }
+        assert!(serde_json::from_str::<Coding>(r#"{"bch":{"m":9,"t":2}}"#).is_err());
+        assert!(serde_json::from_str::<Coding>(r#"{"bch":{"m":3,"t":4}}"#).is_err());
+    }
+
+    #[test]
+    fn convolutional() {
+        let coding: Coding = ::coding::convolutional::Convolutional::default().into();
+        let json = serde_json::to_string(&coding).unwrap();
+        assert_eq!(r#"{"convolutional":{"constraint":7,"generators":[121,91]}}"#, json);
+
+        match serde_json::from_str(&json).unwrap() {
+            Coding::Convolutional { constraint, generators } => assert_eq!((7, (0o171, 0o133)), (constraint, generators)),
+            _ => unreachable!(),
+        }
+        let invalid = r#"{"convolutional":{"constraint":3,"generators":[7,8]}}"#;
+        assert!(serde_json::from_str::<Coding>(invalid).is_err());
+    }
+
+    #[test]
+    fn interleaved() {
+        let coding = Coding::Interleaved {
+            coding: Box::new(Coding::Hamming),
+            interleaver: Interleaver::Block { rows: 16, columns: 7 },
+        };
+        let json = serde_json::to_string(&coding).unwrap();
+        assert_eq!(r#"{"interleaved":{"coding":"hamming","interleaver":{"block":{"rows":16,"columns":7}}}}"#, json);
+
+        match serde_json::from_str(&json).unwrap() {
+            Coding::Interleaved { coding, interleaver: Interleaver::Block { rows, columns } } => {
+                assert_eq!((16, 7), (rows, columns));
+                match *coding {
+                    Coding::Hamming => {}
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+        let invalid = r#"{"interleaved":{"coding":"r3","interleaver":{"block":{"rows":0,"columns":7}}}}"#;
+        assert!(serde_json::from_str::<Coding>(invalid).is_err());
+    }
+}
@@ -1,35 +1,284 @@
 //! - `Compression`
+//!     * `None = Compression`
 //!     * `Rle = Compression`
-//! // TODO
-//!     * `Huffman events:??? = Compression`
-//!     * `Shannon events:??? = Compression`
+//!     * `Huffman table:CodeTable = Compression`
+//!     * `Shannon table:CodeTable = Compression`
+//!     * `Adaptive = Compression`
+//!     * `Arithmetic = Compression`
+//!     * `Lzw max_bits:int = Compression`, dictionary of `2^max_bits` entries, `max_bits` in `[9..24]`
+//!     * `Lzss window_bits:int length_bits:int = Compression`, `window_bits` in `[1..24]`,
+//!       `length_bits` in `[1..16]`
+//!     * `Bwt block_size:int = Compression`, `block_size` is positive
+//!     * `Mtf = Compression`
+//!
+//! - `CodeTable`
+//!     * b64 encoded string of records `symbol:u8 length:u8 code:bytes`,
+//!       where `code` is `length` bits padded with zeros to a whole byte.
+
+use std::collections::HashMap;
+
+use bit_vec::BitVec;
+use serde::{Serialize, Serializer, Deserialize, Deserializer, de};
 
 #[derive(Clone, Debug)]
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Compression {
+    None,
     Rle,
-    // TODO
-    Huffman,
-    Shannon,
+    Huffman { table: CodeTable },
+    Shannon { table: CodeTable },
+    Adaptive,
+    Arithmetic,
+    Lzw {
+        #[serde(deserialize_with = "lzw_max_bits")]
+        max_bits: u8,
+    },
+    #[serde(deserialize_with = "lzss")]
+    Lzss { window_bits: u8, length_bits: u8 },
+    Bwt {
+        #[serde(deserialize_with = "bwt_block_size")]
+        block_size: u32,
+    },
+    Mtf,
+}
+
+/// Prefix code table, i.e. `events` map of `compression::huffman::Huffman`.
+///
+/// Empty table is a placeholder: `Data::from_bytes` replaces it with the optimal table for
+/// the payload being sent.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CodeTable(pub HashMap<u8, BitVec>);
+
+impl Compression {
+    /// Huffman compression with a table to be built by `Data::from_bytes`.
+    pub fn huffman() -> Self {
+        Compression::Huffman { table: CodeTable::default() }
+    }
+
+    /// Shannon–Fano compression with a table to be built by `Data::from_bytes`.
+    pub fn shannon() -> Self {
+        Compression::Shannon { table: CodeTable::default() }
+    }
+
+    /// LZW compression with 12 bits dictionary.
+    pub fn lzw() -> Self {
+        ::compression::lzw::Lzw::default().into()
+    }
+
+    /// LZSS compression with 4 KiB window.
+    pub fn lzss() -> Self {
+        ::compression::lzss::Lzss::default().into()
+    }
+
+    /// Burrows–Wheeler transform of 100 KB blocks.
+    pub fn bwt() -> Self {
+        ::compression::bwt::Bwt::default().into()
+    }
+
+    /// Fill in empty code table with the optimal one for the `input`.
+    pub fn fit(self, input: &[u8]) -> Self {
+        use ::compression::huffman::Huffman;
+        use ::compression::shannon::ShannonFano;
+
+        match self {
+            Compression::Huffman { ref table } if table.is_empty() => {
+                Compression::Huffman { table: CodeTable(Huffman::canonical_for(input).events) }
+            }
+            Compression::Shannon { ref table } if table.is_empty() => {
+                Compression::Shannon { table: CodeTable(ShannonFano::optimal_for(input).huffman.events) }
+            }
+            other => other,
+        }
+    }
+}
+
+impl CodeTable {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut symbols: Vec<&u8> = self.0.keys().collect();
+        symbols.sort();
+
+        let mut bytes = Vec::new();
+        for symbol in symbols {
+            let code = &self.0[symbol];
+            bytes.push(*symbol);
+            bytes.push(code.len() as u8);
+            bytes.extend(code.to_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<CodeTable, ::compression::Error> {
+        let mut map = HashMap::new();
+        let mut offset = 0;
+
+        while offset < bytes.len() {
+            if offset + 2 > bytes.len() {
+                return Err(::compression::Error::ExpectedMoreData);
+            }
+            let symbol = bytes[offset];
+            let length = bytes[offset + 1] as usize;
+            offset += 2;
+
+            let n = (length + 7) / 8;
+            if offset + n > bytes.len() {
+                return Err(::compression::Error::ExpectedMoreData);
+            }
+            let mut code = BitVec::from_bytes(&bytes[offset..offset + n]);
+            code.truncate(length);
+            offset += n;
+
+            map.insert(symbol, code);
+        }
+        Ok(CodeTable(map))
+    }
+}
+
+impl Serialize for CodeTable {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        super::base64::serialize(&self.to_bytes(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for CodeTable {
+    fn deserialize<D>(deserializer: D) -> Result<CodeTable, D::Error>
+        where D: Deserializer<'de>
+    {
+        let bytes = super::base64::deserialize(deserializer)?;
+        CodeTable::from_bytes(&bytes).map_err(|e| de::Error::custom(format!("{:?}", e)))
+    }
+}
+
+/// Reject `max_bits` which `Lzw::new` would panic on.
+fn lzw_max_bits<'de, D>(deserializer: D) -> Result<u8, D::Error>
+    where D: Deserializer<'de>
+{
+    let max_bits = u8::deserialize(deserializer)?;
+    if max_bits < 9 || max_bits > 24 {
+        return Err(de::Error::custom(format!("lzw max_bits must be in range [9..24], got {}", max_bits)));
+    }
+    Ok(max_bits)
+}
+
+/// Reject `window_bits` and `length_bits` which `Lzss::new` would panic on.
+fn lzss<'de, D>(deserializer: D) -> Result<(u8, u8), D::Error>
+    where D: Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    struct Lzss {
+        window_bits: u8,
+        length_bits: u8,
+    }
+
+    let Lzss { window_bits, length_bits } = Lzss::deserialize(deserializer)?;
+    if window_bits < 1 || window_bits > 24 {
+        return Err(de::Error::custom(format!("lzss window_bits must be in range [1..24], got {}", window_bits)));
+    }
+    if length_bits < 1 || length_bits > 16 {
+        return Err(de::Error::custom(format!("lzss length_bits must be in range [1..16], got {}", length_bits)));
+    }
+    Ok((window_bits, length_bits))
+}
+
+/// Reject empty blocks.
+fn bwt_block_size<'de, D>(deserializer: D) -> Result<u32, D::Error>
+    where D: Deserializer<'de>
+{
+    let block_size = u32::deserialize(deserializer)?;
+    if block_size == 0 {
+        return Err(de::Error::custom("bwt block_size must be positive"));
+    }
+    Ok(block_size)
 }
 
 impl Into<Box<::compression::Compression<u8>>> for Compression {
     fn into(self) -> Box<::compression::Compression<u8>> {
+        use ::compression::huffman::Huffman;
+        use ::compression::shannon::ShannonFano;
+
         match self {
+            Compression::None => Box::new(::compression::none::NoCompression),
             Compression::Rle => Box::new(::compression::rle::Rle),
-            // TODO
-            _ => panic!(),
+            Compression::Huffman { table } => Box::new(Huffman::with_map(table.0)),
+            Compression::Shannon { table } => Box::new(ShannonFano::with_map(table.0)),
+            Compression::Adaptive => Box::new(::compression::adaptive::AdaptiveHuffman),
+            Compression::Arithmetic => Box::new(::compression::arithmetic::Arithmetic),
+            Compression::Lzw { max_bits } => Box::new(::compression::lzw::Lzw::new(max_bits as usize)),
+            Compression::Lzss { window_bits, length_bits } => {
+                Box::new(::compression::lzss::Lzss::new(window_bits as usize, length_bits as usize))
+            }
+            Compression::Bwt { block_size } => Box::new(::compression::bwt::Bwt::new(block_size as usize)),
+            Compression::Mtf => Box::new(::compression::mtf::Mtf),
         }
     }
 }
 
+impl From<::compression::none::NoCompression> for Compression {
+    fn from(_: ::compression::none::NoCompression) -> Self {
+        Compression::None
+    }
+}
+
 impl From<::compression::rle::Rle> for Compression {
     fn from(_: ::compression::rle::Rle) -> Self {
         Compression::Rle
     }
 }
 
+impl From<::compression::adaptive::AdaptiveHuffman> for Compression {
+    fn from(_: ::compression::adaptive::AdaptiveHuffman) -> Self {
+        Compression::Adaptive
+    }
+}
+
+impl From<::compression::arithmetic::Arithmetic> for Compression {
+    fn from(_: ::compression::arithmetic::Arithmetic) -> Self {
+        Compression::Arithmetic
+    }
+}
+
+impl From<::compression::lzw::Lzw> for Compression {
+    fn from(lzw: ::compression::lzw::Lzw) -> Self {
+        Compression::Lzw { max_bits: lzw.max_bits as u8 }
+    }
+}
+
+impl From<::compression::lzss::Lzss> for Compression {
+    fn from(lzss: ::compression::lzss::Lzss) -> Self {
+        Compression::Lzss { window_bits: lzss.window_bits as u8, length_bits: lzss.length_bits as u8 }
+    }
+}
+
+impl From<::compression::bwt::Bwt> for Compression {
+    fn from(bwt: ::compression::bwt::Bwt) -> Self {
+        Compression::Bwt { block_size: bwt.block_size as u32 }
+    }
+}
+
+impl From<::compression::mtf::Mtf> for Compression {
+    fn from(_: ::compression::mtf::Mtf) -> Self {
+        Compression::Mtf
+    }
+}
+
+impl From<::compression::huffman::Huffman<u8>> for Compression {
+    fn from(huffman: ::compression::huffman::Huffman<u8>) -> Self {
+        Compression::Huffman { table: CodeTable(huffman.events) }
+    }
+}
+
+impl From<::compression::shannon::ShannonFano<u8>> for Compression {
+    fn from(shannon: ::compression::shannon::ShannonFano<u8>) -> Self {
+        Compression::Shannon { table: CodeTable(shannon.huffman.events) }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -49,4 +298,59 @@ mod test {
             _ => unreachable!(),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn lzw() {
+        use ::compression::Compression as C;
+
+        let input: Vec<u8> = (0..20_000u32).map(|i| (i * i % 251) as u8).collect();
+        let lzw = ::compression::lzw::Lzw::new(10);
+        let compressed = lzw.compress(&input).unwrap();
+
+        let json = serde_json::to_string(&Compression::from(lzw)).unwrap();
+        assert_eq!(r#"{"lzw":{"max_bits":10}}"#, json);
+
+        let de: Compression = serde_json::from_str(&json).unwrap();
+        let de: Box<C<u8>> = de.into();
+        assert_eq!(input, de.decompress(compressed).unwrap());
+
+        assert!(serde_json::from_str::<Compression>(r#"{"lzw":{"max_bits":25}}"#).is_err());
+    }
+
+    #[test]
+    fn parameters() {
+        use ::compression::Compression as C;
+        use ::compression::lzss::Lzss;
+        use ::compression::bwt::Bwt;
+
+        let input: Vec<u8> = (0..20_000u32).map(|i| (i * i % 251) as u8).collect();
+        let stages: Vec<Box<C<u8>>> = vec![Box::new(Lzss::new(8, 3)), Box::new(Bwt::new(1000))];
+        for (stage, compression) in stages.iter().zip(vec![Compression::from(Lzss::new(8, 3)),
+                                                           Compression::from(Bwt::new(1000))]) {
+            let compressed = stage.compress(&input).unwrap();
+            let json = serde_json::to_string(&compression).unwrap();
+            let de: Compression = serde_json::from_str(&json).unwrap();
+            let de: Box<C<u8>> = de.into();
+            assert_eq!(input, de.decompress(compressed).unwrap());
+        }
+
+        assert!(serde_json::from_str::<Compression>(r#"{"lzss":{"window_bits":25,"length_bits":4}}"#).is_err());
+        assert!(serde_json::from_str::<Compression>(r#"{"bwt":{"block_size":0}}"#).is_err());
+    }
+
+    #[test]
+    fn table() {
+        let compression = Compression::huffman().fit(b"abracadabra");
+        let json = serde_json::to_string(&compression).unwrap();
+        assert!(json.starts_with(r#"{"huffman":{"table":""#));
+
+        let de: Compression = serde_json::from_str(&json).unwrap();
+        match (compression, de) {
+            (Compression::Huffman { table }, Compression::Huffman { table: de }) => {
+                assert_eq!(5, table.0.len());
+                assert_eq!(table, de);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
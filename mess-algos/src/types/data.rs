@@ -1,7 +1,14 @@
 //! - `Data`
-//!     * `Data coding:Coding compression:Compression length:int bytes:bytes = Data`, where `length` is # bits.
+//!     * `Data coding:Coding compression:Vector<Compression> length:int bytes:bytes checksum:flags.0?int = Data`,
+//!       where `length` is # bits, and `checksum` is optional CRC-32 of the original payload.
+//!
+//! `compression` is a pipeline of stages applied in order.  Single stage is (de)serialized as
+//! a bare `Compression` rather than a vector of one element.  Payload which is not compressed
+//! at all has a single `none` stage; `none` stages of longer pipelines are dropped, and empty
+//! vector is accepted in place of `none`.
 
 use super::*;
+use ::checksum::Crc;
 
 #[derive(Clone, Debug)]
 #[derive(Serialize, Deserialize)]
@@ -9,38 +16,103 @@ use super::*;
 pub enum Data {
     Data {
         coding: Coding,
-        compression: Compression,
+        #[serde(with = "pipeline")]
+        compression: Vec<Compression>,
         length: i64,
         #[serde(with = "base64")]
         bytes: Vec<u8>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        checksum: Option<u32>,
     }
 }
 
 impl Data {
     pub fn from_bytes(bytes: &[u8], compression: Compression, coding: Coding) -> Result<Data, Error> {
-        use ::compression::Compression;
+        Data::from_bytes_pipeline(bytes, vec![compression], coding)
+    }
+
+    /// compress with every stage of the `compression` in order, then encode.
+    pub fn from_bytes_pipeline(bytes: &[u8], compression: Vec<Compression>, coding: Coding) -> Result<Data, Error> {
+        let (compression, compressed) = compress(bytes, compression)?;
+        Ok(Data::encode(compressed, compression, coding))
+    }
+
+    /// try every pipeline of `candidates(bytes.len())`, and keep the smallest one.
+    pub fn from_bytes_auto(bytes: &[u8], coding: Coding) -> Result<Data, Error> {
+        Data::from_bytes_best(bytes, candidates(bytes.len()), coding)
+    }
+
+    /// try every pipeline of the `candidates`, and keep the smallest one.
+    ///
+    /// Size of a candidate is its compressed payload plus code tables sent along in the header.
+    /// Chosen pipeline is recorded in `compression`, so the receiving side needs nothing extra.
+    /// Payload is sent uncompressed if no candidate succeeds.
+    pub fn from_bytes_best(bytes: &[u8], candidates: Vec<Vec<Compression>>, coding: Coding) -> Result<Data, Error> {
+        let (compression, compressed) = candidates
+            .into_iter()
+            .filter_map(|pipeline| compress(bytes, pipeline).ok())
+            .min_by_key(|&(ref compression, ref compressed)| {
+                compressed.len() + compression.iter().map(table_bits).sum::<usize>()
+            })
+            .unwrap_or_else(|| (vec![], ::bit_vec::BitVec::from_bytes(bytes)));
+
+        Ok(Data::encode(compressed, compression, coding))
+    }
+
+    fn encode(compressed: ::bit_vec::BitVec, compression: Vec<Compression>, coding: Coding) -> Data {
         use ::coding::Coding;
-        use ::bit_vec::BitVec;
 
-        let compressor: Box<Compression<u8>> = compression.clone().into();
-        let compressed: BitVec = compressor.compress(bytes).map_err(Error::Compression)?;
         let coder: Box<Coding> = coding.clone().into();
-        let encoded: BitVec = coder.encode(compressed);
+        let encoded = coder.encode(compressed);
 
         let length = encoded.len() as i64;
         let bytes = encoded.to_bytes();
 
-        Ok(Data::Data {
+        Data::Data {
             coding,
-            compression,
+            compression: normalize(compression),
             length,
             bytes,
-        })
+            checksum: None,
+        }
+    }
+
+    /// attach CRC-32 of the original `payload`, to be verified by `into_bytes`.
+    pub fn with_checksum(self, payload: &[u8]) -> Data {
+        match self {
+            Data::Data { coding, compression, length, bytes, .. } => Data::Data {
+                coding,
+                compression,
+                length,
+                bytes,
+                checksum: Some(Crc::crc32().checksum(payload)),
+            },
+        }
+    }
+
+    /// Same data as received through `noise`: encoded bits are corrupted, the rest is intact.
+    ///
+    /// Noise is reproducible if `seed` is given, see `noise::seeded`.
+    pub fn through(self, noise: Noise, seed: Option<u64>) -> Data {
+        use ::bit_vec::BitVec;
+
+        let channel: Box<::noise::Channel> = noise.into();
+        match self {
+            Data::Data { coding, compression, length, bytes, checksum } => {
+                let bits = BitVec::from_bytes(&bytes);
+                let bits = match seed {
+                    Some(seed) => channel.transmit_seeded(&bits, seed),
+                    None => channel.transmit(&bits),
+                };
+                Data::Data { coding, compression, length, bytes: bits.to_bytes(), checksum }
+            }
+        }
     }
 
     /// decode and decompress bytes
     pub fn into_bytes(self) -> Result<Vec<u8>, Error> {
         use ::compression::Compression;
+        use ::compression::pipeline::Pipeline;
         use ::coding::Coding;
         use ::bit_vec::BitVec;
 
@@ -50,6 +122,7 @@ impl Data {
                 compression,
                 length,
                 bytes,
+                checksum,
             } => {
                 let mut bits: BitVec = BitVec::from_bytes(&bytes);
                 bits.truncate(length as usize);
@@ -57,12 +130,19 @@ impl Data {
                 let decoder: Box<Coding> = coding.into();
                 let (decoded, stats) = decoder.decode(bits);
 
-                if stats.corrected < stats.detected {
+                if stats.uncorrectable() > 0 {
                     Err(stats)?;
                 }
 
-                let compressor: Box<Compression<u8>> = compression.into();
-                let decompressed = compressor.decompress(decoded)?;
+                let stages: Vec<Box<Compression<u8>>> = compression.into_iter().map(Into::into).collect();
+                let decompressed = Pipeline::new(stages).decompress(decoded)?;
+
+                if let Some(expected) = checksum {
+                    let actual = Crc::crc32().checksum(&decompressed);
+                    if actual != expected {
+                        return Err(Error::ChecksumMismatch { expected, actual });
+                    }
+                }
 
                 Ok(decompressed)
             }
@@ -70,11 +150,99 @@ impl Data {
     }
 }
 
+/// Apply stages in order, filling in their code tables.
+fn compress(bytes: &[u8], compression: Vec<Compression>) -> Result<(Vec<Compression>, ::bit_vec::BitVec), Error> {
+    use ::compression::Compression;
+    use ::compression::pipeline::pack;
+    use ::bit_vec::BitVec;
+
+    let n = compression.len();
+    let mut input: Vec<u8> = bytes.to_vec();
+    let mut compressed: BitVec = BitVec::from_bytes(bytes);
+    let mut stages = Vec::with_capacity(n);
+
+    for (i, stage) in compression.into_iter().enumerate() {
+        // code tables are built for the input of their own stage
+        let stage = stage.fit(&input);
+        let compressor: Box<Compression<u8>> = stage.clone().into();
+        compressed = compressor.compress(&input)?;
+        if i + 1 < n {
+            input = pack(compressed.clone());
+        }
+        stages.push(stage);
+    }
+
+    Ok((stages, compressed))
+}
+
+/// Drop pass-through stages, so that no compression at all has the only form: single `None` stage.
+pub fn normalize(compression: Vec<Compression>) -> Vec<Compression> {
+    let stages: Vec<Compression> = compression
+        .into_iter()
+        .filter(|stage| match *stage { Compression::None => false, _ => true })
+        .collect();
+    if stages.is_empty() { vec![Compression::None] } else { stages }
+}
+
+/// `one_or_many` pipeline, normalized on both sides.
+mod pipeline {
+    use serde::{Serializer, Deserializer};
+
+    use super::{one_or_many, normalize, Compression};
+
+    pub fn serialize<S>(compression: &Vec<Compression>, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        one_or_many::serialize(&normalize(compression.clone()), serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Compression>, D::Error>
+        where D: Deserializer<'de>
+    {
+        one_or_many::deserialize(deserializer).map(normalize)
+    }
+}
+
+/// Payloads larger than this are not tried with slow compressions by `candidates`.
+pub const SLOW_COMPRESSION_LIMIT: usize = 1 << 20;
+
+/// Pipelines tried by `Data::from_bytes_auto` for payload of `size` bytes, including none at all.
+///
+/// Adaptive Huffman, arithmetic coding and BWT are skipped for payloads over
+/// `SLOW_COMPRESSION_LIMIT`.
+pub fn candidates(size: usize) -> Vec<Vec<Compression>> {
+    let mut candidates = vec![
+        vec![Compression::None],
+        vec![Compression::Rle],
+        vec![Compression::huffman()],
+        vec![Compression::shannon()],
+        vec![Compression::lzw()],
+        vec![Compression::lzss()],
+        vec![Compression::lzss(), Compression::huffman()],
+    ];
+    if size <= SLOW_COMPRESSION_LIMIT {
+        candidates.push(vec![Compression::Adaptive]);
+        candidates.push(vec![Compression::Arithmetic]);
+        candidates.push(vec![Compression::bwt(), Compression::Mtf, Compression::Rle, Compression::huffman()]);
+    }
+    candidates
+}
+
+/// Bits of code table sent in the header.
+fn table_bits(compression: &Compression) -> usize {
+    match *compression {
+        Compression::Huffman { ref table } | Compression::Shannon { ref table } => 8 * table.to_bytes().len(),
+        _ => 0,
+    }
+}
+
 /// chain errors
 #[derive(Clone, Debug)]
 pub enum Error {
     Decode(::coding::Stats),
     Compression(::compression::Error),
+    /// payload was decoded and decompressed, but it is not what was sent.
+    ChecksumMismatch { expected: u32, actual: u32 },
 }
 
 impl From<::coding::Stats> for Error {
@@ -113,4 +281,195 @@ mod test {
         let bytes = data.into_bytes().unwrap();
         assert_eq!(b"rust", bytes.as_slice());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn code_table() {
+        let bytes = b"she sells sea shells by the sea shore";
+        for compression in vec![Compression::None, Compression::Rle, Compression::huffman(), Compression::shannon(),
+                                   Compression::Adaptive, Compression::Arithmetic, Compression::lzw()] {
+            let data = Data::from_bytes(bytes, compression, Coding::Hamming).unwrap();
+            let str = serde_json::to_string(&data).unwrap();
+            let data: Data = serde_json::from_str(&str).unwrap();
+            assert_eq!(&bytes[..], &*data.into_bytes().unwrap());
+        }
+    }
+
+    #[test]
+    fn pipeline() {
+        let bytes = b"she sells sea shells by the sea shore";
+        let pipeline = vec![Compression::bwt(), Compression::Mtf, Compression::Rle, Compression::huffman()];
+        let data = Data::from_bytes_pipeline(bytes, pipeline, Coding::Hamming).unwrap();
+
+        let json = serde_json::to_value(&data).unwrap();
+        assert_eq!(json["compression"][0], json!({"bwt": {"block_size": 100000}}));
+        assert_eq!(json["compression"][2], json!("rle"));
+        assert!(json["compression"][3]["huffman"]["table"].is_string());
+
+        let str = serde_json::to_string(&json).unwrap();
+        let data: Data = serde_json::from_str(&str).unwrap();
+        assert_eq!(&bytes[..], &*data.into_bytes().unwrap());
+
+        let data = Data::from_bytes_pipeline(bytes, vec![], Coding::Hamming).unwrap();
+        let json = serde_json::to_value(&data).unwrap();
+        assert_eq!(json["compression"], json!("none"));
+        assert_eq!(&bytes[..], &*data.into_bytes().unwrap());
+    }
+
+    #[test]
+    fn raw() {
+        let data = Data::from_bytes(b"rust", Compression::None, Coding::None).unwrap();
+        let json = serde_json::to_value(&data).unwrap();
+        assert_eq!(json, json!({
+            "coding": "none",
+            "compression": "none",
+            "length": 32,
+            "bytes": "cnVzdA==",
+        }));
+        assert_eq!(b"rust", &*data.into_bytes().unwrap());
+    }
+
+    #[test]
+    fn none() {
+        let none = r#"{"coding":"none","compression":"none","length":32,"bytes":"cnVzdA=="}"#;
+        for str in [none,
+                    r#"{"coding":"none","compression":[],"length":32,"bytes":"cnVzdA=="}"#,
+                    r#"{"coding":"none","compression":["none"],"length":32,"bytes":"cnVzdA=="}"#].iter() {
+            let data: Data = serde_json::from_str(str).unwrap();
+            assert_eq!(none, serde_json::to_string(&data).unwrap());
+            assert_eq!(b"rust", &*data.into_bytes().unwrap());
+        }
+
+        let data = Data::from_bytes_pipeline(b"rust", vec![Compression::None, Compression::Rle], Coding::None).unwrap();
+        assert_eq!(json!("rle"), serde_json::to_value(&data).unwrap()["compression"]);
+    }
+
+    #[test]
+    fn uncorrectable() {
+        use ::bit_vec::BitVec;
+
+        let data = Data::from_bytes(b"rust", Compression::None, Coding::Secded).unwrap();
+        let Data::Data { coding, compression, length, bytes, checksum } = data;
+
+        // two flips in the first block
+        let mut bits = BitVec::from_bytes(&bytes);
+        for &i in [1, 5].iter() {
+            let bit = bits[i];
+            bits.set(i, !bit);
+        }
+        let data = Data::Data { coding, compression, length, bytes: bits.to_bytes(), checksum };
+        match data.into_bytes() {
+            Err(Error::Decode(stats)) => assert_eq!(1, stats.uncorrectable()),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn single_value() {
+        let str = r#"{"coding":"parity","compression":"rle","length":50,"bytes":"9h5XqeZ6QA=="}"#;
+        let data: Data = serde_json::from_str(str).unwrap();
+        match data {
+            Data::Data { ref compression, .. } => assert_eq!(1, compression.len()),
+        }
+        assert_eq!(b"rust", &*data.into_bytes().unwrap());
+    }
+
+    #[test]
+    fn auto() {
+        // nothing to squeeze out of two bytes
+        let data = Data::from_bytes_auto(b"hi", Coding::Parity).unwrap();
+        match data {
+            Data::Data { ref compression, .. } => match compression[..] {
+                [Compression::None] => {}
+                _ => panic!("{:?}", compression),
+            },
+        }
+        assert_eq!(b"hi", &*data.into_bytes().unwrap());
+
+        let text = "to be or not to be, that is the question. ".repeat(20);
+        let data = Data::from_bytes_auto(text.as_bytes(), Coding::Hamming).unwrap();
+        let rle = Data::from_bytes(text.as_bytes(), Compression::Rle, Coding::Hamming).unwrap();
+        match (&data, &rle) {
+            (&Data::Data { ref compression, length, .. }, &Data::Data { length: rle_length, .. }) => {
+                assert!(compression.iter().all(|c| match *c { Compression::None => false, _ => true }));
+                assert!(length < rle_length);
+            }
+        }
+
+        let str = serde_json::to_string(&data).unwrap();
+        let data: Data = serde_json::from_str(&str).unwrap();
+        assert_eq!(text.as_bytes(), &*data.into_bytes().unwrap());
+    }
+
+    #[test]
+    fn best() {
+        let text = "to be or not to be, that is the question. ".repeat(20);
+        let pipelines = vec![vec![Compression::Rle], vec![Compression::lzw()]];
+        let data = Data::from_bytes_best(text.as_bytes(), pipelines, Coding::Hamming).unwrap();
+        match data {
+            Data::Data { ref compression, .. } => match compression[..] {
+                [Compression::Lzw { .. }] => {}
+                _ => panic!("{:?}", compression),
+            },
+        }
+        assert_eq!(text.as_bytes(), &*data.into_bytes().unwrap());
+
+        // nothing to choose from
+        let data = Data::from_bytes_best(b"hi", vec![], Coding::Parity).unwrap();
+        match data {
+            Data::Data { ref compression, .. } => match compression[..] {
+                [Compression::None] => {}
+                _ => panic!("{:?}", compression),
+            },
+        }
+        assert_eq!(b"hi", &*data.into_bytes().unwrap());
+
+        assert!(candidates(SLOW_COMPRESSION_LIMIT + 1).len() < candidates(0).len());
+    }
+
+    #[test]
+    fn checksum() {
+        use ::bit_vec::BitVec;
+
+        let data = Data::from_bytes(b"rust", Compression::None, Coding::R3).unwrap().with_checksum(b"rust");
+        let json = serde_json::to_value(&data).unwrap();
+        assert_eq!(json!(0xe132_82a0u32), json["checksum"]);
+
+        let str = serde_json::to_string(&json).unwrap();
+        let data: Data = serde_json::from_str(&str).unwrap();
+        assert_eq!(b"rust", &*data.clone().into_bytes().unwrap());
+
+        // two flips in one triple are "corrected" into a wrong bit
+        let Data::Data { coding, compression, length, bytes, checksum } = data;
+        let mut bits = BitVec::from_bytes(&bytes);
+        for &i in [0, 1].iter() {
+            let bit = bits[i];
+            bits.set(i, !bit);
+        }
+        let data = Data::Data { coding, compression, length, bytes: bits.to_bytes(), checksum };
+        match data.into_bytes() {
+            Err(Error::ChecksumMismatch { expected, .. }) => assert_eq!(0xe132_82a0, expected),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn through() {
+        let payload = b"noisy channel, noisy channel";
+        let data = Data::from_bytes(payload, Compression::None, Coding::R5).unwrap().with_checksum(payload);
+
+        let clean = data.clone().through(Noise::None, None);
+        assert_eq!(payload, &*clean.into_bytes().unwrap());
+
+        // the same seed makes the same damage
+        let noise = Noise::Bsc { p: 0.05 };
+        let (a, b) = (data.clone().through(noise.clone(), Some(3)), data.clone().through(noise, Some(3)));
+        match (a, b, data) {
+            (Data::Data { bytes: a, length, .. }, Data::Data { bytes: b, .. }, Data::Data { bytes, .. }) => {
+                assert_eq!(a, b);
+                assert_eq!(bytes.len(), a.len());
+                assert_eq!(payload.len() as i64 * 8 * 5, length);
+                assert!(a != bytes);
+            }
+        }
+    }
+}
@@ -19,8 +19,9 @@
 //!     * `Updates updates:Vector<Update> = Updates`
 //!
 //! - `Update`
-//!     * `TextUpdate from:Username to:Username payload:Data = Update`
-//!     * `FileUpdate from:Username to:Username meta:FileMeta file_id:FileId = Update`
+//!     * `TextUpdate from:Username to:Username payload:Data id:flags.0?int = Update`
+//!     * `FileUpdate from:Username to:Username meta:FileMeta file_id:FileId id:flags.0?int = Update`
+//!     * `RetransmitUpdate from:Username to:Username id:int = Update`
 //!
 //! - `FileMeta`:
 //!     * `FileMeta name:string size:int mime:string = FileMeta`, where `size` is # bytes.
@@ -33,19 +34,49 @@
 //!     * `EmptyFile = DownloadedFile`
 //!
 //! - `Data`
-//!     * `Data coding:Coding compression:Compression length:int bytes:bytes = Data`, where `length` is # bits.
+//!     * `Data coding:Coding compression:Vector<Compression> length:int bytes:bytes checksum:flags.0?int = Data`,
+//!       where `length` is # bits.
+//!       Single `Compression` is accepted in place of a vector of one element.
+//!       Payload which is not compressed has `none` compression, or empty vector.
 //!
 //! - `Compression`
+//!     * `None = Compression`
 //!     * `Rle = Compression`
-//! // TODO
-//!     * `Huffman events:??? = Compression`
-//!     * `Shannon events:??? = Compression`
+//!     * `Huffman table:CodeTable = Compression`
+//!     * `Shannon table:CodeTable = Compression`
+//!     * `Adaptive = Compression`
+//!     * `Arithmetic = Compression`
+//!     * `Lzw = Compression`
+//!     * `Lzss = Compression`
+//!     * `Bwt = Compression`
+//!     * `Mtf = Compression`
+//!
+//! - `CodeTable`
+//!     * b64 encoded string of records `symbol:u8 length:u8 code:bytes`
 //!
 //! - `Coding`
+//!     * `None = Coding`
 //!     * `Hamming = Coding`
+//!     * `Secded = Coding`
+//!     * `GenericHamming r:int = Coding`
+//!     * `ReedSolomon n:int k:int = Coding`
+//!     * `Bch m:int t:int = Coding`
+//!     * `Convolutional constraint:int generators:(int, int) = Coding`
+//!     * `Interleaved coding:Coding interleaver:Interleaver = Coding`
 //!     * `Parity = Coding`
 //!     * `R3 = Coding`
 //!     * `R5 = Coding`
+//!
+//! - `Interleaver`
+//!     * `Block rows:int columns:int = Interleaver`
+//!     * `Convolutional branches:int delay:int = Interleaver`
+//!
+//! - `Noise`
+//!     * `None = Noise`
+//!     * `Bsc p:double = Noise`
+//!     * `Bursts error_rate:double burst_length:double = Noise`
+//!     * `Erasure p:double = Noise`
+//!     * `InsertionDeletion insertion:double deletion:double = Noise`
 
 
 pub mod login_result;
@@ -58,6 +89,7 @@ pub mod data;
 pub mod coding;
 pub mod compression;
 pub mod downloaded_file;
+pub mod noise;
 
 pub use self::login_result::LoginResult;
 pub use self::online::Online;
@@ -66,9 +98,10 @@ pub use self::updates::Updates;
 pub use self::file_meta::FileMeta;
 pub use self::file_id::FileId;
 pub use self::data::Data;
-pub use self::coding::Coding;
-pub use self::compression::Compression;
+pub use self::coding::{Coding, Interleaver};
+pub use self::compression::{Compression, CodeTable};
 pub use self::downloaded_file::DownloadedFile;
+pub use self::noise::Noise;
 
 pub type Username = String;
 
@@ -102,6 +135,38 @@ pub mod base64 {
     }
 }
 
+/// `Vector<T>` which is written as a bare `T` when it has exactly one element.
+pub mod one_or_many {
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+
+    pub fn serialize<T, S>(items: &Vec<T>, serializer: S) -> Result<S::Ok, S::Error>
+        where T: Serialize,
+              S: Serializer
+    {
+        match items.len() {
+            1 => items[0].serialize(serializer),
+            _ => items.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+        where T: Deserialize<'de>,
+              D: Deserializer<'de>
+    {
+        match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(item) => Ok(vec![item]),
+            OneOrMany::Many(items) => Ok(items),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
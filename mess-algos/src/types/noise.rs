@@ -0,0 +1,136 @@
+//! - `Noise`
+//!     * `None = Noise`
+//!     * `Bsc p:double = Noise`, independent bit flips with probability `p`
+//!     * `Bursts error_rate:double burst_length:double = Noise`, Gilbert–Elliott bursts of errors
+//!     * `Erasure p:double = Noise`, lost bits guessed by the receiver
+//!     * `InsertionDeletion insertion:double deletion:double = Noise`
+
+use serde::{Deserialize, Deserializer, de};
+
+#[derive(Clone, Debug, PartialEq)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Noise {
+    None,
+    Bsc {
+        #[serde(deserialize_with = "probability")]
+        p: f64,
+    },
+    #[serde(deserialize_with = "bursts")]
+    Bursts { error_rate: f64, burst_length: f64 },
+    Erasure {
+        #[serde(deserialize_with = "probability")]
+        p: f64,
+    },
+    #[serde(deserialize_with = "insertion_deletion")]
+    InsertionDeletion { insertion: f64, deletion: f64 },
+}
+
+fn check_probability<E: de::Error>(p: f64) -> Result<f64, E> {
+    if p >= 0.0 && p <= 1.0 {
+        Ok(p)
+    } else {
+        Err(de::Error::custom("probability must be in range [0..1]"))
+    }
+}
+
+/// Reject probabilities out of `[0..1]`.
+fn probability<'de, D>(deserializer: D) -> Result<f64, D::Error>
+    where D: Deserializer<'de>
+{
+    check_probability(f64::deserialize(deserializer)?)
+}
+
+/// Gilbert model can not make more than a half of bits wrong.
+fn bursts<'de, D>(deserializer: D) -> Result<(f64, f64), D::Error>
+    where D: Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    struct Bursts {
+        error_rate: f64,
+        burst_length: f64,
+    }
+
+    let Bursts { error_rate, burst_length } = Bursts::deserialize(deserializer)?;
+    if !(error_rate >= 0.0 && error_rate < 0.5) {
+        return Err(de::Error::custom("error rate must be in range [0..0.5)"));
+    }
+    if !(burst_length >= 1.0) {
+        return Err(de::Error::custom("burst length must be at least 1"));
+    }
+    Ok((error_rate, burst_length))
+}
+
+/// Reject certain insertions, which would never end.
+fn insertion_deletion<'de, D>(deserializer: D) -> Result<(f64, f64), D::Error>
+    where D: Deserializer<'de>
+{
+    #[derive(Deserialize)]
+    struct InsertionDeletion {
+        insertion: f64,
+        deletion: f64,
+    }
+
+    let InsertionDeletion { insertion, deletion } = InsertionDeletion::deserialize(deserializer)?;
+    if insertion >= 1.0 {
+        return Err(de::Error::custom("insertion must be in range [0..1)"));
+    }
+    Ok((check_probability(insertion)?, check_probability(deletion)?))
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Noise::None
+    }
+}
+
+impl Into<Box<::noise::Channel>> for Noise {
+    fn into(self) -> Box<::noise::Channel> {
+        use ::noise::*;
+
+        match self {
+            Noise::None => Box::new(NoiseLevel::CLEAN),
+            Noise::Bsc { p } => Box::new(Bsc::new(p)),
+            Noise::Bursts { error_rate, burst_length } => {
+                Box::new(GilbertElliott::bursts(error_rate, burst_length))
+            }
+            Noise::Erasure { p } => Box::new(Erasure::new(p)),
+            Noise::InsertionDeletion { insertion, deletion } => {
+                Box::new(InsertionDeletion::new(insertion, deletion))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::{self, from_value};
+
+    #[test]
+    fn serde() {
+        assert_eq!(r#""none""#, serde_json::to_string(&Noise::None).unwrap());
+        assert_eq!(json!({"bsc": {"p": 0.05}}), serde_json::to_value(&Noise::Bsc { p: 0.05 }).unwrap());
+
+        for noise in [
+            Noise::None,
+            Noise::Bsc { p: 0.01 },
+            Noise::Bursts { error_rate: 0.05, burst_length: 16.0 },
+            Noise::Erasure { p: 0.1 },
+            Noise::InsertionDeletion { insertion: 0.001, deletion: 0.002 },
+        ].iter() {
+            let value = serde_json::to_value(noise).unwrap();
+            assert_eq!(*noise, from_value::<Noise>(value).unwrap());
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(from_value::<Noise>(json!({"bsc": {"p": 1.5}})).is_err());
+        assert!(from_value::<Noise>(json!({"erasure": {"p": -0.1}})).is_err());
+        assert!(from_value::<Noise>(json!({"bursts": {"error_rate": 0.5, "burst_length": 16}})).is_err());
+        assert!(from_value::<Noise>(json!({"bursts": {"error_rate": 0.1, "burst_length": 0.5}})).is_err());
+        assert!(from_value::<Noise>(json!({"insertiondeletion": {"insertion": 1, "deletion": 0}})).is_err());
+        assert!(from_value::<Noise>(json!({"insertiondeletion": {"insertion": 0, "deletion": 2}})).is_err());
+    }
+}
@@ -1,6 +1,10 @@
 //! - `Update`
-//!     * `TextUpdate from:Username to:Username payload:Data = Update`
-//!     * `FileUpdate from:Username to:Username meta:FileMeta file_id:FileId = Update`
+//!     * `TextUpdate from:Username to:Username payload:Data id:flags.0?int = Update`
+//!     * `FileUpdate from:Username to:Username meta:FileMeta file_id:FileId id:flags.0?int = Update`
+//!     * `RetransmitUpdate from:Username to:Username id:int = Update`, where `from` failed to
+//!       decode message `id` sent by `to`.
+//!
+//! `id` of a message is chosen by its sender, and stays the same when message is retransmitted.
 
 use super::*;
 
@@ -12,11 +16,21 @@ pub enum Update {
         from: Username,
         to: Username,
         payload: Data,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<i64>,
     },
     FileUpdate {
         from: Username,
         to: Username,
         meta: FileMeta,
         file_id: FileId,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<i64>,
+    },
+    /// must come last: untagged text and file updates would match it too
+    RetransmitUpdate {
+        from: Username,
+        to: Username,
+        id: i64,
     },
 }
\ No newline at end of file
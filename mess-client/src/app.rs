@@ -3,6 +3,18 @@ use algos::methods::ClientMethod;
 
 use mime_guess;
 
+use std::collections::HashMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Coding of the first attempt to deliver a message, and of retransmissions after it.
+const CODINGS: [Coding; 3] = [Coding::Hamming, Coding::R3, Coding::R5];
+/// How many times a message may be retransmitted before the sender gives up.
+const RETRY_BUDGET: usize = 3;
+/// Sent messages older than this are forgotten, and can not be retransmitted.
+const SENT_TIMEOUT_SECS: u64 = 10 * 60;
+/// Only this many latest sent messages are kept for retransmission.
+const MAX_SENT: usize = 64;
+
 
 #[derive(Eq, PartialEq)]
 pub enum State {
@@ -24,6 +36,18 @@ pub enum AppEvent {
     Log { message: String, error: bool },
 }
 
+/// Message sent to the peer, kept in case the peer fails to decode it.
+struct Outgoing {
+    /// the only user allowed to ask for retransmission
+    to: Username,
+    mode: Mode,
+    /// text, or path to a file
+    input: String,
+    /// retransmissions so far
+    retries: usize,
+    sent_at: Instant,
+}
+
 pub struct App {
     state: State,
     /// user can't send new messages until he finishes with current.
@@ -40,7 +64,19 @@ pub struct App {
     /// oldest messages come first
     history: Vec<Update>,
 
+    /// token of admin methods: with it, hotkeys set noise of the channel to the peer
+    admin_token: Option<String>,
+    /// noise of the channel to the peer, as sent to server; unknown without `admin_token`
     noise: NoiseLevel,
+    model: ChannelModel,
+    /// seed of the noise on the way to the peer, random if not given in command line
+    noise_seed: Option<u64>,
+    compression: CompressionMode,
+
+    /// sent messages by id
+    sent: HashMap<i64, Outgoing>,
+    /// id of the next message
+    next_id: i64,
 
     // app internals
     events: (Sender<AppEvent>, Receiver<AppEvent>),
@@ -52,7 +88,7 @@ pub struct App {
 }
 
 impl App {
-    pub fn new(me: Username, peer: Username) -> App {
+    pub fn new(me: Username, peer: Username, admin_token: Option<String>, noise_seed: Option<u64>, compression: CompressionMode) -> App {
         let screen = AlternateScreen::from(io::stdout());
         let backend = TermionBackend::new().unwrap();
         let mut t = Terminal::new(backend).unwrap();
@@ -70,7 +106,15 @@ impl App {
             peer,
             history: Vec::new(),
 
-            noise: NoiseLevel::Noise015,
+            admin_token,
+            noise: NoiseLevel::new(0.15),
+            model: Default::default(),
+            noise_seed,
+            compression,
+
+            sent: HashMap::new(),
+            // ids are unique across restarts
+            next_id: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64 * 1000).unwrap_or(0),
 
             events: channel(),
 
@@ -84,6 +128,7 @@ impl App {
         self.state = State::Error;
         self.status = match error {
             Error::Reason(reason) => reason,
+            Error::Data(DataError::ChecksumMismatch { .. }) => "Checksum mismatch: received data is corrupted".into(),
             _ => format!("{:?}", error),
         };
     }
@@ -110,7 +155,10 @@ impl App {
                     LoginResult::LoginOk { username } => {
                         self.me = username;
                         self.state = State::LoggedIn;
-                        self.status = format!("Logged in as {}", self.me);
+                        self.status = match self.admin_token {
+                            Some(_) => format!("Logged in as {}", self.me),
+                            None => format!("Logged in as {}, noise is set by server", self.me),
+                        };
                     }
                     LoginResult::LoginErr => {
                         let msg = format!("Login error: username \"{}\" can not be used", &self.me);
@@ -146,6 +194,10 @@ impl App {
     pub fn event_loop(&mut self) -> Result<()> {
         self.set_up();
         self.login();
+        if self.state == State::LoggedIn {
+            // server keeps its own noise until told otherwise
+            self.configure_noise();
+        }
         self.spawn_updates_loop();
 
         while self.state != State::Exit {
@@ -226,13 +278,14 @@ impl App {
 
         for update in self.history.iter() {
             match *update {
-                Update::TextUpdate { ref from, ref to, ref payload } => {
+                Update::TextUpdate { ref from, ref to, ref payload, .. } => {
                     s.push_str(&self.format_meta(from, to));
                     let msg = match payload.clone().into_bytes() {
                         Ok(vec) => match String::from_utf8(vec) {
                             Ok(s) => escape_brackets(&s),
                             Err(_) => "{red UTF-8 error}".into(),
                         },
+                        Err(DataError::ChecksumMismatch { .. }) => "{red checksum mismatch}".into(),
                         Err(_) => "{red decoding error}".into(),
                     };
                     s.push_str(&msg);
@@ -245,6 +298,7 @@ impl App {
                     s.push_str(&format!("[{} {{fg=red \"{}\"}} {} bytes]\n",
                                         mime, escape_brackets(name), size))
                 }
+                Update::RetransmitUpdate { .. } => {}
             }
         }
 
@@ -272,6 +326,7 @@ impl App {
                     from: msg.from,
                     to: msg.to,
                     payload: msg.payload,
+                    id: msg.id,
                 });
 
                 self.input.reset();
@@ -284,6 +339,7 @@ impl App {
                     to: file.to,
                     meta: file.meta,
                     file_id: file.file_id,
+                    id: file.id,
                 });
                 self.input.reset();
                 self.sending = false;
@@ -296,22 +352,129 @@ impl App {
             AppEvent::Updates(updates) => {
                 let Updates::Updates { updates } = updates;
                 for update in updates {
-                    if let &Update::FileUpdate { ref meta, ref file_id, .. } = &update {
-                        match self.download_file(meta, file_id) {
-                            Ok(_) => {
-                                self.status = format!("Download file: done");
-                                self.state = State::LoggedIn;
-                            }
-                            Err(e) => self.error(e),
-                        }
+                    match update {
+                        Update::RetransmitUpdate { from, id, .. } => self.retransmit(from, id),
+                        update => self.receive(update),
                     }
-                    self.history.push(update);
                 }
             }
         }
         Ok(())
     }
 
+    /// Download files, and ask for retransmission of whatever fails to decode.
+    fn receive(&mut self, update: Update) {
+        let corrupted = match update {
+            Update::TextUpdate { ref from, ref payload, id: Some(id), .. } => {
+                if payload.clone().into_bytes().is_err() { Some((from.clone(), id)) } else { None }
+            }
+            Update::FileUpdate { ref from, ref meta, ref file_id, id, .. } => {
+                match self.download_file(meta, file_id) {
+                    Ok(_) => {
+                        self.status = format!("Download file: done");
+                        self.state = State::LoggedIn;
+                        None
+                    }
+                    Err(Error::Data(_)) if id.is_some() => Some((from.clone(), id.unwrap())),
+                    Err(e) => {
+                        self.error(e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        if let Some((from, id)) = corrupted {
+            self.request_retransmit(from, id);
+        }
+        self.push_history(update);
+    }
+
+    /// Retransmitted messages take place of their corrupted copies.
+    fn push_history(&mut self, update: Update) {
+        fn key(update: &Update) -> Option<(&str, i64)> {
+            match *update {
+                Update::TextUpdate { ref from, id: Some(id), .. } |
+                Update::FileUpdate { ref from, id: Some(id), .. } => Some((from.as_str(), id)),
+                _ => None,
+            }
+        }
+
+        let position = key(&update).and_then(|k| self.history.iter().position(|old| key(old) == Some(k)));
+        match position {
+            Some(i) => self.history[i] = update,
+            None => self.history.push(update),
+        }
+    }
+
+    fn request_retransmit(&self, to: Username, id: i64) {
+        let method = RequestRetransmit { from: self.me.clone(), to, id };
+        let tx = self.events.0.clone();
+        thread::spawn(move || {
+            let (message, error) = match method.invoke(&Connection::default()) {
+                Ok(true) => (format!("Message from {} is corrupted, asked for retransmission", method.to), false),
+                Ok(false) => ("Request retransmission: server error".into(), true),
+                Err(e) => (format!("Request retransmission: {:?}", e), true),
+            };
+            tx.send(AppEvent::Log { message, error }).unwrap();
+        });
+    }
+
+    /// Send message `id` once again with stronger coding, unless retry budget is exhausted.
+    ///
+    /// Requests of anyone but the recipient of the message are ignored.
+    fn retransmit(&mut self, from: Username, id: i64) {
+        self.expire_sent();
+        match self.sent.get(&id) {
+            Some(outgoing) if outgoing.to == from => {}
+            // not ours, given up already, or sent to someone else
+            _ => return,
+        }
+        let to = from;
+
+        let (mode, input, retries) = match self.sent.get_mut(&id) {
+            Some(outgoing) if outgoing.retries < RETRY_BUDGET => {
+                outgoing.retries += 1;
+                (outgoing.mode, outgoing.input.clone(), outgoing.retries)
+            }
+            Some(_) => {
+                self.sent.remove(&id);
+                let message = format!("Message to {} is lost after {} retransmissions", to, RETRY_BUDGET);
+                self.events.0.send(AppEvent::Log { message, error: true }).unwrap();
+                return;
+            }
+            None => return,
+        };
+
+        let me = self.me.clone();
+        let compression = self.compression.clone();
+        let tx = self.events.0.clone();
+        thread::spawn(move || {
+            let coding = coding_for(retries);
+            let result = match mode {
+                Mode::Text => send_text(me, to, &input, &compression, coding, id).map(|_| ()),
+                Mode::File => send_file(me, to, input, &compression, coding, id).map(|_| ()),
+            };
+            let (message, error) = match result {
+                Ok(()) => (format!("Retransmission {} of {}: done", retries, RETRY_BUDGET), false),
+                Err(e) => (format!("Retransmission {} of {}: {:?}", retries, RETRY_BUDGET, e), true),
+            };
+            tx.send(AppEvent::Log { message, error }).unwrap();
+        });
+    }
+
+    /// Forget sent messages which are too old, or too many, to be retransmitted.
+    fn expire_sent(&mut self) {
+        let timeout = Duration::from_secs(SENT_TIMEOUT_SECS);
+        self.sent.retain(|_, outgoing| outgoing.sent_at.elapsed() < timeout);
+
+        // ids grow with time, so the smallest one is the oldest
+        while self.sent.len() > MAX_SENT {
+            let oldest = *self.sent.keys().min().unwrap();
+            self.sent.remove(&oldest);
+        }
+    }
+
     fn handle_input(&mut self, event: Event) -> Result<()> {
         match event {
             Event::Key(Key::Esc) => self.status.clear(),
@@ -324,6 +487,10 @@ impl App {
             }
 
             Event::Key(Key::F(5)) => self.history.clear(),
+            Event::Key(Key::F(6)) => self.switch_model(),
+            Event::Key(Key::F(7)) => self.switch_compression(),
+            Event::Key(Key::PageUp) => self.set_noise(self.noise.increase()),
+            Event::Key(Key::PageDown) => self.set_noise(self.noise.decrease()),
             Event::Key(Key::Char('\n')) => self.send()?,
             Event::Key(Key::Up) => {
                 let last = self.history.iter().rev()
@@ -351,13 +518,66 @@ impl App {
     }
 
     fn adjust_noise(&mut self, level: u8) {
-        self.noise = match level {
-            1 => NoiseLevel::Noise001,
-            2 => NoiseLevel::Noise005,
-            3 => NoiseLevel::Noise015,
-            _ => NoiseLevel::Clean,
+        let noise = match level {
+            1 => NoiseLevel::new(0.01),
+            2 => NoiseLevel::new(0.05),
+            3 => NoiseLevel::new(0.15),
+            _ => NoiseLevel::CLEAN,
+        };
+        self.set_noise(noise);
+    }
+
+    fn set_noise(&mut self, noise: NoiseLevel) {
+        if self.admin_token.is_none() {
+            self.noise_is_read_only();
+            return;
+        }
+        self.noise = noise;
+        self.configure_noise();
+    }
+
+    fn switch_model(&mut self) {
+        if self.admin_token.is_none() {
+            self.noise_is_read_only();
+            return;
+        }
+        self.model = self.model.next();
+        self.configure_noise();
+    }
+
+    fn noise_is_read_only(&mut self) {
+        self.state = State::Error;
+        self.status = "Noise is set by server admin, start client with --admin-token to change it".into();
+    }
+
+    fn switch_compression(&mut self) {
+        self.compression = self.compression.next();
+        self.status = format!("Compression: {}", self.compression.name());
+    }
+
+    /// Ask server to simulate the channel to the peer, as admin.  Seeds given in command line
+    /// restart on every change, so that a session may be replayed.
+    fn configure_noise(&self) {
+        let token = match self.admin_token {
+            Some(ref token) => token.clone(),
+            None => return,
+        };
+        let method = SetNoise {
+            token,
+            from: Some(self.me.clone()),
+            to: self.peer.clone(),
+            noise: self.model.noise(self.noise),
+            seed: self.noise_seed,
         };
-        info(&self.events.0, format!("Noise level: {}", self.noise.to_str()));
+        let message = format!("Noise level: {}, channel model: {}", self.noise, self.model.name());
+        let tx = self.events.0.clone();
+        thread::spawn(move || {
+            match method.invoke(&Connection::default()) {
+                Ok(true) => info(&tx, message),
+                Ok(false) => tx.send(AppEvent::SendFailed { error: "Set noise: refused by server".into() }).unwrap(),
+                Err(e) => tx.send(AppEvent::SendFailed { error: e.into() }).unwrap(),
+            }
+        });
     }
 
     fn send(&mut self) -> Result<()> {
@@ -372,59 +592,41 @@ impl App {
         };
         let me = self.me.clone();
         let peer = self.peer.clone();
+        let compression = self.compression.clone();
 
         let tx = self.events.0.clone();
 
+        let id = self.next_id;
+        self.next_id += 1;
+        self.sent.insert(id, Outgoing {
+            to: peer.clone(),
+            mode: self.mode,
+            input: input.clone(),
+            retries: 0,
+            sent_at: Instant::now(),
+        });
+        self.expire_sent();
+
         match self.mode {
             Mode::Text => {
-                let noise = self.noise;
                 thread::spawn(move || {
                     info(&tx, "Send message: compressing...");
                     thread::sleep(::std::time::Duration::from_millis(500));
 
-                    let data = Data::from_bytes(
-                        input.as_bytes(),
-                        Compression::Rle,
-                        Coding::Hamming,
-                    ).unwrap();
-
-                    let Data::Data { coding, compression, length, bytes } = data;
-                    let bits = BitVec::from_bytes(&bytes);
-                    let bits = noise.apply(bits.iter()).collect::<BitVec>();
-                    let bytes = bits.to_bytes();
-                    let data = Data::Data { coding, compression, length, bytes };
-
-                    let method = SendText {
-                        from: me,
-                        to: peer,
-                        payload: data,
-                    };
-
-                    info(&tx, "Send message: compressed and encoded; sending...");
-
-                    let result = method.invoke(&Connection::default());
-                    thread::sleep(Duration::from_millis(500));
-
-                    match result {
-                        Ok(answer) => {
-                            if answer == false {
-                                tx.send(AppEvent::SendFailed { error: "server error".into() }).unwrap();
-                            } else {
-                                info(&tx, "Send message: done");
-                                tx.send(AppEvent::SentText(method)).unwrap();
-                            }
-                        }
-                        Err(e) => {
-                            tx.send(AppEvent::SendFailed { error: e.into() }).unwrap();
+                    let event = match send_text(me, peer, &input, &compression, coding_for(0), id) {
+                        Ok(method) => {
+                            info(&tx, "Send message: done");
+                            AppEvent::SentText(method)
                         }
-                    }
+                        Err(e) => AppEvent::SendFailed { error: e },
+                    };
+                    tx.send(event).unwrap();
                 });
             }
             Mode::File => {
-                let noise = self.noise;
                 thread::spawn(move || {
                     info(&tx, "Sending file...");
-                    let event = match send_file(me, peer, input, noise) {
+                    let event = match send_file(me, peer, input, &compression, coding_for(0), id) {
                         Ok(file) => AppEvent::SentFile(file),
                         Err(e) => AppEvent::SendFailed { error: e },
                     };
@@ -515,7 +717,27 @@ impl Drop for App {
     }
 }
 
-fn send_file<P: AsRef<Path>>(me: Username, peer: Username, path: P, noise: NoiseLevel) -> Result<UploadFile> {
+/// Coding of the message after `retries` retransmissions.
+fn coding_for(retries: usize) -> Coding {
+    CODINGS[retries.min(CODINGS.len() - 1)].clone()
+}
+
+fn send_text(me: Username, peer: Username, text: &str, compression: &CompressionMode, coding: Coding, id: i64) -> Result<SendText> {
+    let data = compression.data(text.as_bytes(), coding)?.with_checksum(text.as_bytes());
+
+    let method = SendText {
+        from: me,
+        to: peer,
+        payload: data,
+        id: Some(id),
+    };
+
+    let answer = method.invoke(&Connection::default())?;
+    if answer == false { Err("Send message: server error")?; }
+    Ok(method)
+}
+
+fn send_file<P: AsRef<Path>>(me: Username, peer: Username, path: P, compression: &CompressionMode, coding: Coding, id: i64) -> Result<UploadFile> {
     thread::sleep(Duration::from_millis(500));
 
     let conn = Connection::default();
@@ -535,17 +757,7 @@ fn send_file<P: AsRef<Path>>(me: Username, peer: Username, path: P, noise: Noise
         mime,
     };
 
-    let data = Data::from_bytes(
-        &content,
-        Compression::Rle,
-        Coding::Hamming,
-    ).unwrap();
-
-    let Data::Data { coding, compression, length, bytes } = data;
-    let bits = BitVec::from_bytes(&bytes);
-    let bits = noise.apply(bits.iter()).collect::<BitVec>();
-    let bytes = bits.to_bytes();
-    let data = Data::Data { coding, compression, length, bytes };
+    let data = compression.data(&content, coding)?.with_checksum(&content);
 
     let method = UploadFile {
         from: me,
@@ -553,6 +765,7 @@ fn send_file<P: AsRef<Path>>(me: Username, peer: Username, path: P, noise: Noise
         meta,
         file_id,
         payload: data,
+        id: Some(id),
     };
 
     let answer = method.invoke(&conn)?;
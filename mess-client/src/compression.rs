@@ -0,0 +1,73 @@
+use imports::*;
+
+/// How messages and files are compressed before sending.
+#[derive(Clone, Debug)]
+pub enum CompressionMode {
+    /// smallest of all pipelines worth trying for the payload, see `data::candidates`
+    Auto,
+    /// single compression stage, or none at all
+    Fixed(Compression),
+}
+
+/// Stages which may be chosen instead of `Auto`, in order of switching.
+const STAGES: [&str; 9] = ["none", "rle", "huffman", "shannon", "adaptive", "arithmetic", "lzw", "lzss", "bwt"];
+
+impl Default for CompressionMode {
+    fn default() -> Self {
+        CompressionMode::Auto
+    }
+}
+
+impl CompressionMode {
+    /// Mode by its name, as given in command line.
+    pub fn parse(name: &str) -> Option<Self> {
+        let stage = match name {
+            "auto" => return Some(CompressionMode::Auto),
+            "none" => Compression::None,
+            "rle" => Compression::Rle,
+            "huffman" => Compression::huffman(),
+            "shannon" => Compression::shannon(),
+            "adaptive" => Compression::Adaptive,
+            "arithmetic" => Compression::Arithmetic,
+            "lzw" => Compression::lzw(),
+            "lzss" => Compression::lzss(),
+            "bwt" => Compression::bwt(),
+            _ => return None,
+        };
+        Some(CompressionMode::Fixed(stage))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            CompressionMode::Auto => "auto",
+            CompressionMode::Fixed(Compression::None) => "none",
+            CompressionMode::Fixed(Compression::Rle) => "rle",
+            CompressionMode::Fixed(Compression::Huffman { .. }) => "huffman",
+            CompressionMode::Fixed(Compression::Shannon { .. }) => "shannon",
+            CompressionMode::Fixed(Compression::Adaptive) => "adaptive",
+            CompressionMode::Fixed(Compression::Arithmetic) => "arithmetic",
+            CompressionMode::Fixed(Compression::Lzw { .. }) => "lzw",
+            CompressionMode::Fixed(Compression::Lzss { .. }) => "lzss",
+            CompressionMode::Fixed(Compression::Bwt { .. }) => "bwt",
+            CompressionMode::Fixed(Compression::Mtf) => "mtf",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let position = STAGES.iter().position(|&name| name == self.name());
+        let next = match position {
+            None => STAGES[0],
+            Some(i) if i + 1 < STAGES.len() => STAGES[i + 1],
+            Some(_) => "auto",
+        };
+        CompressionMode::parse(next).unwrap()
+    }
+
+    /// Compress `bytes`, then encode them with `coding`.
+    pub fn data(&self, bytes: &[u8], coding: Coding) -> ::std::result::Result<Data, DataError> {
+        match *self {
+            CompressionMode::Auto => Data::from_bytes_auto(bytes, coding),
+            CompressionMode::Fixed(ref stage) => Data::from_bytes(bytes, stage.clone(), coding),
+        }
+    }
+}
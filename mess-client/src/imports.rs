@@ -27,6 +27,7 @@ pub use termion::event::{Event, Key};
 pub use termion::screen::AlternateScreen;
 
 pub use algos::types::*;
+pub use algos::types::data::Error as DataError;
 pub use algos::methods::*;
 pub use algos::noise::NoiseLevel;
 
@@ -37,4 +38,6 @@ pub use connection::Connection;
 pub use gui::view::*;
 pub use gui::control::*;
 
-pub use mode::Mode;
\ No newline at end of file
+pub use mode::Mode;
+pub use model::ChannelModel;
+pub use compression::CompressionMode;
\ No newline at end of file
@@ -14,23 +14,60 @@ extern crate rand;
 extern crate bit_vec;
 
 mod app;
+mod compression;
 mod error;
 mod imports;
 mod connection;
 mod gui;
 mod logo;
 mod mode;
+mod model;
 
 use imports::*;
 
 fn usage() -> ! {
     let name = env::args().next().unwrap();
-    println!("usage: {} <username> <peer username>", name);
+    println!("usage: {} [--admin-token <token>] [--noise-seed <seed>] [--compression <name>] <username> <peer username>", name);
+    println!("compressions: auto, none, rle, huffman, shannon, adaptive, arithmetic, lzw, lzss, bwt");
     exit(1);
 }
 
 fn main() {
-    let mut args = env::args().skip(1);
+    let mut args = env::args().skip(1).peekable();
+    let mut admin_token = None;
+    let mut noise_seed = None;
+    let mut compression = CompressionMode::default();
+    loop {
+        match args.peek().map(String::as_str) {
+            Some("--admin-token") => {
+                args.next();
+                match args.next() {
+                    Some(token) => admin_token = Some(token),
+                    None => usage(),
+                }
+            }
+            Some("--noise-seed") => {
+                args.next();
+                match args.next().and_then(|seed| seed.parse::<u64>().ok()) {
+                    Some(seed) => noise_seed = Some(seed),
+                    None => usage(),
+                }
+            }
+            Some("--compression") => {
+                args.next();
+                match args.next().and_then(|name| CompressionMode::parse(&name)) {
+                    Some(mode) => compression = mode,
+                    None => usage(),
+                }
+            }
+            _ => break,
+        }
+    }
+    if noise_seed.is_some() && admin_token.is_none() {
+        // noise, and so its seed, is set up with admin methods only
+        println!("--noise-seed requires --admin-token");
+        usage();
+    }
     let me = match args.next() {
         Some(ok) => ok,
         None => usage(),
@@ -40,7 +77,7 @@ fn main() {
         None => usage(),
     };
 
-    let mut app = app::App::new(me, peer);
+    let mut app = app::App::new(me, peer, admin_token, noise_seed, compression);
 
     let result = catch_unwind(AssertUnwindSafe(|| {
         app.event_loop().unwrap();
@@ -0,0 +1,54 @@
+use imports::*;
+
+/// Channel model which server simulates noise with, while `NoiseLevel` sets its error rate.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ChannelModel {
+    /// independent bit flips
+    Bsc,
+    /// Gilbert–Elliott bursts of errors
+    Bursts,
+    /// lost bits, guessed by the receiver
+    Erasure,
+    /// inserted and deleted bits
+    Slips,
+}
+
+/// Mean length of bursts of errors, in bits.
+const BURST_LENGTH: f64 = 16.0;
+
+impl Default for ChannelModel {
+    fn default() -> Self {
+        ChannelModel::Bsc
+    }
+}
+
+impl ChannelModel {
+    pub fn name(self) -> &'static str {
+        match self {
+            ChannelModel::Bsc => "BSC",
+            ChannelModel::Bursts => "Gilbert-Elliott bursts",
+            ChannelModel::Erasure => "erasure",
+            ChannelModel::Slips => "insertion/deletion",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ChannelModel::Bsc => ChannelModel::Bursts,
+            ChannelModel::Bursts => ChannelModel::Erasure,
+            ChannelModel::Erasure => ChannelModel::Slips,
+            ChannelModel::Slips => ChannelModel::Bsc,
+        }
+    }
+
+    /// Noise of this model with `level` chance of a bit to be damaged.
+    pub fn noise(self, level: NoiseLevel) -> Noise {
+        let p = level.to_f64();
+        match self {
+            ChannelModel::Bsc => Noise::Bsc { p },
+            ChannelModel::Bursts => Noise::Bursts { error_rate: p.min(0.49), burst_length: BURST_LENGTH },
+            ChannelModel::Erasure => Noise::Erasure { p },
+            ChannelModel::Slips => Noise::InsertionDeletion { insertion: (p / 2.0).min(0.99), deletion: p / 2.0 },
+        }
+    }
+}
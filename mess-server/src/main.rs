@@ -40,6 +40,10 @@ pub struct App {
     pub files: HashMap<FileId, File>,
     /// last used file id
     pub last_id: i64,
+    /// noise of channels by sender and receiver, or by receiver alone for all senders
+    pub links: HashMap<(Option<Username>, Username), Link>,
+    /// token of admin methods, which are refused if `None`
+    pub admin_token: Option<String>,
 }
 
 #[derive(Debug)]
@@ -50,10 +54,20 @@ pub struct User {
 
 #[derive(Debug)]
 pub struct File {
+    pub from: Username,
+    pub to: Username,
     pub meta: FileMeta,
     pub payload: Data,
 }
 
+/// Simulated channel, which damages payloads on their way to the receiver.
+#[derive(Debug)]
+pub struct Link {
+    pub noise: Noise,
+    /// seed for the next payload, or random noise if `None`
+    pub seed: Option<u64>,
+}
+
 pub struct JsonKey;
 
 impl Key for JsonKey { type Value = serde_json::Value; }
@@ -61,12 +75,32 @@ impl Key for JsonKey { type Value = serde_json::Value; }
 impl Key for App { type Value = App; }
 
 impl App {
-    pub fn new() -> Self {
+    pub fn new(admin_token: Option<String>) -> Self {
         App {
             users: HashMap::new(),
             pending: HashSet::new(),
             files: HashMap::new(),
             last_id: 0,
+            links: HashMap::new(),
+            admin_token,
+        }
+    }
+
+    /// Whether `token` grants access to admin methods.
+    pub fn is_admin(&self, token: &str) -> bool {
+        self.admin_token.as_ref().map(|admin| admin == token).unwrap_or(false)
+    }
+
+    /// Deliver `payload` sent by `from` to `to` through the channel between them.
+    pub fn transmit(&mut self, from: &str, to: &str, payload: Data) -> Data {
+        let key = (Some(from.to_string()), to.to_string());
+        let key = if self.links.contains_key(&key) { key } else { (None, key.1) };
+        match self.links.get_mut(&key) {
+            Some(link) => {
+                let seed = link.next_seed();
+                payload.through(link.noise.clone(), seed)
+            }
+            None => payload,
         }
     }
 
@@ -77,6 +111,14 @@ impl App {
     }
 }
 
+impl Link {
+    pub fn next_seed(&mut self) -> Option<u64> {
+        let seed = self.seed;
+        self.seed = seed.map(|seed| seed.wrapping_add(1));
+        seed
+    }
+}
+
 impl User {
     pub fn new(username: Username) -> Self {
         User {
@@ -92,7 +134,7 @@ impl User {
     }
 }
 
-fn app_handler() -> Chain {
+fn app_handler(admin_token: Option<String>) -> Chain {
     let mut r = router::Router::new();
     r.post("/", default, "default");
 
@@ -108,9 +150,11 @@ fn app_handler() -> Chain {
     route::<SendFile>(&mut r);
     route::<UploadFile>(&mut r);
     route::<DownloadFile>(&mut r);
+    route::<SetNoise>(&mut r);
+    route::<RequestRetransmit>(&mut r);
 
     let mut chain = Chain::new(r);
-    chain.link(State::<App>::both(App::new()));
+    chain.link(State::<App>::both(App::new(admin_token)));
 
     // every response is a json
     chain.link_after(|_: &mut Request, res: Response| -> IronResult<Response> {
@@ -191,6 +235,15 @@ pub fn setup_log(level: log::LogLevelFilter) {
 fn main() {
     setup_log(log::LogLevelFilter::Info);
 
-    let handler = app_handler();
+    // admin methods, i.e. `setNoise`, are disabled unless token is given
+    let admin_token = match std::env::var("MESS_ADMIN_TOKEN") {
+        Ok(token) => if token.is_empty() { None } else { Some(token) },
+        Err(_) => None,
+    };
+    if admin_token.is_none() {
+        info!("MESS_ADMIN_TOKEN is not set, admin methods are disabled");
+    }
+
+    let handler = app_handler(admin_token);
     Iron::new(handler).http("0.0.0.0:3000").unwrap();
 }
@@ -3,7 +3,7 @@ use super::*;
 impl ServerMethod<App> for DownloadFile {
     fn handle(self, app: &mut App) -> DownloadedFile {
         match app.files.remove(&self.file_id) {
-            Some(file) => DownloadedFile::File { data: file.payload },
+            Some(file) => DownloadedFile::File { data: app.transmit(&file.from, &file.to, file.payload) },
             None => DownloadedFile::EmptyFile {},
         }
     }
@@ -2,8 +2,20 @@ use super::*;
 
 impl ServerMethod<App> for GetUpdates {
     fn handle(self, app: &mut App) -> Self::Answer {
-        let user: &mut User = app.get_or_new_user(self.username.clone());
-        let updates: Vec<Update> = user.inbox.drain(..).collect();
+        let updates: Vec<Update> = {
+            let user: &mut User = app.get_or_new_user(self.username.clone());
+            user.inbox.drain(..).collect()
+        };
+        // files are damaged on download instead
+        let updates = updates.into_iter()
+            .map(|update| match update {
+                Update::TextUpdate { from, to, payload, id } => {
+                    let payload = app.transmit(&from, &to, payload);
+                    Update::TextUpdate { from, to, payload, id }
+                }
+                update => update,
+            })
+            .collect();
         Updates::Updates { updates }
     }
 }
@@ -5,8 +5,10 @@ mod send_file;
 mod send_text;
 mod upload_file;
 mod download_file;
+mod set_noise;
+mod request_retransmit;
 
 // for `use super::*;` inside submodules.
 pub use ::algos::types::*;
 pub use ::algos::methods::*;
-pub use ::{App, User, File};
+pub use ::{App, User, File, Link};
@@ -0,0 +1,17 @@
+use super::*;
+
+impl ServerMethod<App> for RequestRetransmit {
+    fn handle(self, app: &mut App) -> bool {
+        if self.to == self.from || !app.users.contains_key(&self.to) {
+            return false;
+        }
+        app.get_or_new_user(self.to.clone())
+           .inbox
+           .push_back(Update::RetransmitUpdate {
+               from: self.from,
+               to: self.to,
+               id: self.id,
+           });
+        true
+    }
+}
@@ -11,6 +11,7 @@ impl ServerMethod<App> for SendText {
                from: self.from,
                to: self.to,
                payload: self.payload,
+               id: self.id,
            });
         true
     }
@@ -0,0 +1,19 @@
+use super::*;
+
+impl ServerMethod<App> for SetNoise {
+    fn handle(self, app: &mut App) -> bool {
+        if !app.is_admin(&self.token) {
+            warn!("setNoise refused: bad admin token");
+            return false;
+        }
+        let valid = |username: &Username| User::validate_username(username);
+        if !valid(&self.to) || !self.from.as_ref().map(valid).unwrap_or(true) {
+            return false;
+        }
+        info!("noise from {:?} to {}: {:?}, seed {:?}", self.from, self.to, self.noise, self.seed);
+
+        // clean link still overrides noise of the receiver
+        app.links.insert((self.from, self.to), Link { noise: self.noise, seed: self.seed });
+        true
+    }
+}
@@ -7,7 +7,12 @@ impl ServerMethod<App> for UploadFile {
         if !app.pending.remove(&self.file_id) ||
             app.files.contains_key(&self.file_id) { return false; };
 
-        app.files.insert(self.file_id.clone(), File { meta: self.meta.clone(), payload: self.payload });
+        app.files.insert(self.file_id.clone(), File {
+            from: self.from.clone(),
+            to: self.to.clone(),
+            meta: self.meta.clone(),
+            payload: self.payload,
+        });
 
         app.get_or_new_user(self.to.clone())
            .inbox
@@ -16,6 +21,7 @@ impl ServerMethod<App> for UploadFile {
                to: self.to,
                meta: self.meta,
                file_id: self.file_id,
+               id: self.id,
            });
         true
     }
@@ -12,7 +12,7 @@ CREATE TABLE `file` (
 DROP TABLE IF EXISTS `compression`;
 CREATE TABLE `compression` (
   `file_name`       TEXT    NOT NULL,
-  `compression`     TEXT    NOT NULL CHECK (`compression` IN ('huffman', 'rle', 'shannon')),
+  `compression`     TEXT    NOT NULL,
   `compress_rate`   REAL    NOT NULL,
   `size_compressed` INTEGER NOT NULL,
   `time_compress`   INTEGER NOT NULL,
@@ -25,8 +25,8 @@ DROP TABLE IF EXISTS `coding`;
 CREATE TABLE `coding` (
   `file_name`       TEXT    NOT NULL,
   `compression`     TEXT    NOT NULL,
-  `coding_name`     TEXT    NOT NULL CHECK (`coding_name` IN ('hamming', 'parity', 'r3', 'r5')),
-  `noise_rate`      TEXT    NOT NULL CHECK (`noise_rate` IN ('0.01', '0.05', '0.15')),
+  `coding_name`     TEXT    NOT NULL,
+  `noise_rate`      TEXT    NOT NULL, /* bit flip probability, or channel model with parameters */
   `redundancy_rate` REAL    NOT NULL,
   `size_decoded`    INTEGER NOT NULL, /* bazicali, duplicate of `compression`.`size_compressed` */
   `size_encoded`    INTEGER NOT NULL,
@@ -35,6 +35,9 @@ CREATE TABLE `coding` (
   `not_corrected`   INTEGER NOT NULL CHECK (`not_corrected` <= `size_decoded`),
   `time_encode`     INTEGER NOT NULL,
   `time_decode`     INTEGER NOT NULL,
+  `noise_seed`      INTEGER NOT NULL, /* replays the noise, see `algos::noise::seeded` */
+  `iterations`      INTEGER NOT NULL, /* spent by iterative decoders, 0 for the rest */
+  `syndrome_weight` INTEGER NOT NULL, /* checks left unsatisfied by iterative decoders, 0 for the rest */
   PRIMARY KEY (`file_name`, `compression`, `coding_name`, `noise_rate`),
   FOREIGN KEY (`file_name`) REFERENCES `file` (`file_name`),
   FOREIGN KEY (`file_name`, `compression`) REFERENCES `compression` (`file_name`, `compression`)
@@ -40,11 +40,17 @@ pub struct Coding {
     pub compression: String,
     pub coding_name: String,
     pub noise_rate: String,
+    /// seed of `algos::noise::seeded` generator the noise was made with
+    pub noise_seed: i64,
     pub redundancy_rate: f64,
     pub size_decoded: i64,
     pub size_encoded: i64,
     pub corrected: i64,
     pub detected: i64,
+    /// iterations spent by iterative decoders, zero for the rest
+    pub iterations: i64,
+    /// checks left unsatisfied by iterative decoders, zero for the rest
+    pub syndrome_weight: i64,
     pub not_corrected: i64,
     pub time_encode: i64,
     pub time_decode: i64,
@@ -155,7 +161,7 @@ impl Coding {
     {
         connection(|conn| {
             let sql = "\
-                SELECT redundancy_rate, size_decoded, size_encoded, corrected, detected, not_corrected, time_encode, time_decode
+                SELECT redundancy_rate, size_decoded, size_encoded, corrected, detected, not_corrected, time_encode, time_decode, noise_seed, iterations, syndrome_weight
                   FROM coding
                  WHERE file_name = ?1
                    AND coding_name = ?2
@@ -178,6 +184,9 @@ impl Coding {
                         not_corrected: row.get(5)?,
                         time_encode: row.get(6)?,
                         time_decode: row.get(7)?,
+                        noise_seed: row.get(8)?,
+                        iterations: row.get(9)?,
+                        syndrome_weight: row.get(10)?,
                     })
                 })
         })
@@ -197,8 +206,11 @@ impl Coding {
                                            detected,
                                            not_corrected,
                                            time_encode,
-                                           time_decode)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
+                                           time_decode,
+                                           noise_seed,
+                                           iterations,
+                                           syndrome_weight)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)
             ";
             let mut stmt = conn.prepare_cached(sql)?;
             stmt.execute(params![
@@ -214,6 +226,9 @@ impl Coding {
                 &self.not_corrected,
                 &self.time_encode,
                 &self.time_decode,
+                &self.noise_seed,
+                &self.iterations,
+                &self.syndrome_weight,
             ])?;
             Ok(())
         })
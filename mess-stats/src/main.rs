@@ -8,11 +8,12 @@ extern crate mime;
 extern crate mime_guess;
 extern crate threadpool;
 extern crate num_cpus;
+extern crate rand;
 extern crate algos;
 
 use std::path::PathBuf;
 use std::fs::{self};
-use std::io::{Read, Write};
+use std::io::Read;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
@@ -20,42 +21,68 @@ use bit_vec::BitVec;
 use threadpool::ThreadPool;
 
 use algos::compression::Compression;
+use algos::compression::none::*;
 use algos::compression::rle::*;
 use algos::compression::huffman::*;
 use algos::compression::shannon::*;
-
-use algos::coding::Coding;
+use algos::compression::adaptive::*;
+use algos::compression::arithmetic::*;
+use algos::compression::lzss::*;
+use algos::compression::lzw::*;
+use algos::compression::bwt::*;
+use algos::compression::mtf::*;
+use algos::compression::pipeline::*;
+
+use algos::coding::{Coding, SoftDecoding, hard_decision};
+use algos::coding::none::NoCoding;
 use algos::coding::repetition3::Repetition3;
 use algos::coding::repetition5::Repetition5;
 use algos::coding::parity::Parity;
 use algos::coding::hamming::Hamming;
+use algos::coding::extended_hamming::ExtendedHamming;
+use algos::coding::generic_hamming::GenericHamming;
+use algos::coding::reed_solomon::ReedSolomon;
+use algos::coding::bch::Bch;
+use algos::coding::convolutional::Convolutional;
+use algos::coding::ldpc::{Ldpc, ParityCheck, Decoder};
+use algos::coding::interleaver::{Interleaved, Interleaver};
 
-use algos::noise::NoiseLevel;
+use algos::noise::{seeded, NoiseLevel, Awgn, Channel, GilbertElliott, Erasure, InsertionDeletion};
 
 mod db;
 
 type Result<T> = std::result::Result<T, Error>;
 
 fn main() {
-    let arg = std::env::args().nth(1).expect("expected argument: file path");
-    let path = PathBuf::from(arg);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let bench = args.iter().any(|arg| arg == "--bench-huffman");
+    let compression = args.iter().any(|arg| arg == "--compression");
+    let path = args.into_iter().find(|arg| !arg.starts_with("--")).map(PathBuf::from);
+
+    if compression {
+        if let Err(e) = run() {
+            eprintln!("compression statistics failed: {:?}", e);
+        }
+    }
+
+    let path = match path {
+        Some(path) => path,
+        None if compression => return,
+        None => panic!("expected argument: file path"),
+    };
 
     let mut file = fs::File::open(path).unwrap();
     let mut content: Vec<u8> = Vec::new();
     file.read_to_end(&mut content).unwrap();
 
+    if bench {
+        if let Err(e) = bench_huffman(&content) {
+            eprintln!("huffman benchmark failed: {:?}", e);
+        }
+    }
     run_coding(&Hamming, "hamming", BitVec::from_bytes(&content));
-
-    return;
-
-    let db_file = db::File {
-        file_name: "".into(),
-        file_type: mime_guess::guess_mime_type(&path),
-        file_size: 0,
-    };
-    let compression = Huffman::<u8>::optimal_for(&*content);
-    live_with_it("huffman", &compression, &*content, &db_file).unwrap();
-    run().unwrap();
+    soft_vs_hard(&BitVec::from_bytes(&content));
+    over_channels(&BitVec::from_bytes(&content));
 }
 
 fn run_coding(coding: &Coding, coding_name: &str, data: BitVec) {
@@ -69,12 +96,15 @@ fn run_coding(coding: &Coding, coding_name: &str, data: BitVec) {
         file_name: String::new(),
         compression: String::new(),
         coding_name: coding_name.into(),
-        noise_rate: NoiseLevel::Clean.to_str().into(),
+        noise_rate: NoiseLevel::CLEAN.to_string(),
+        noise_seed: 0,
         redundancy_rate,
         size_decoded: data.len() as i64,
         size_encoded: encoded.len() as i64,
         corrected: 0,
         detected: 0,
+        iterations: 0,
+        syndrome_weight: 0,
         not_corrected: 0,
         time_encode,
         time_decode: 0,
@@ -83,6 +113,121 @@ fn run_coding(coding: &Coding, coding_name: &str, data: BitVec) {
     println!("{:?}", db_coding);
 }
 
+/// Residual bit error rates of hard and soft decision decoding over AWGN channel.
+fn soft_vs_hard(data: &BitVec) {
+    for &(coding, coding_name) in [
+        (&Repetition3 as &SoftDecoding, "r3"),
+        (&Repetition5, "r5"),
+        (&Hamming, "hamming"),
+    ].iter() {
+        let encoded = coding.encode(data.clone());
+
+        for &snr_db in [0.0, 2.0, 4.0, 6.0].iter() {
+            // same noise for every run
+            let received = Awgn::new(snr_db).apply_with(encoded.iter(), &mut seeded(0));
+
+            let (hard, _) = coding.decode(hard_decision(&received));
+            let (soft, _) = coding.decode_soft(&received);
+
+            let ber = |decoded: &BitVec| distance(data, decoded) as f64 / data.len() as f64;
+            println!("{} at {} dB: hard {:.6}, soft {:.6}", coding_name, snr_db, ber(&hard), ber(&soft));
+        }
+    }
+}
+
+/// Residual errors of codings over the bit error rate sweep and other channel models.
+fn over_channels(data: &BitVec) {
+    let channels = channels();
+
+    for &(coding, coding_name) in [
+        (&NoCoding as &Coding, "none"),
+        (&Repetition3, "r3"),
+        (&Repetition5, "r5"),
+        (&Parity, "parity"),
+        (&Hamming, "hamming"),
+        (&ExtendedHamming, "secded"),
+        (&GenericHamming::new(4), "hamming(15,11)"),
+        (&GenericHamming::new(5), "hamming(31,26)"),
+        (&GenericHamming::new(6), "hamming(63,57)"),
+        (&ReedSolomon::new(255, 223), "rs(255,223)"),
+        (&ReedSolomon::new(15, 11), "rs(15,11)"),
+        (&Bch::new(4, 2), "bch(15,7)"),
+        (&Bch::new(5, 3), "bch(31,16)"),
+        (&Convolutional::new(3, (0o7, 0o5)), "conv(3;7,5)"),
+        (&Convolutional::default(), "conv(7;171,133)"),
+        (&Ldpc::new(ParityCheck::regular(1008, 3, 6, 1), Decoder::BitFlipping, 50), "ldpc(1008;3,6)+flip"),
+        (&Ldpc::new(ParityCheck::regular(1008, 3, 6, 1), Decoder::MinSum, 50), "ldpc(1008;3,6)+minsum"),
+        (&Interleaved::new(Hamming, Interleaver::Block { rows: 32, columns: 7 }), "hamming+block(32x7)"),
+        (&Interleaved::new(ReedSolomon::new(255, 223), Interleaver::Block { rows: 8, columns: 8 * 255 }),
+         "rs(255,223)+block(8x2040)"),
+    ].iter() {
+        let (encoded, time_encode) = profile(|| {
+            Ok(coding.encode(data.clone()))
+        }).unwrap();
+
+        for &(ref channel, ref noise_rate) in channels.iter() {
+            // recorded, so that any row can be replayed
+            let noise_seed = rand::random::<u32>() as u64;
+            let received = channel.transmit_seeded(&encoded, noise_seed);
+            let ((decoded, stats), time_decode) = profile(move || {
+                Ok(coding.decode(received))
+            }).unwrap();
+
+            let db_coding = db::Coding {
+                file_name: String::new(),
+                compression: String::new(),
+                coding_name: coding_name.into(),
+                noise_rate: noise_rate.clone(),
+                noise_seed: noise_seed as i64,
+                redundancy_rate: encoded.len() as f64 / data.len() as f64,
+                size_decoded: data.len() as i64,
+                size_encoded: encoded.len() as i64,
+                corrected: stats.corrected as i64,
+                detected: stats.detected as i64,
+                iterations: stats.iterations as i64,
+                syndrome_weight: stats.syndrome_weight as i64,
+                not_corrected: distance(data, &decoded) as i64,
+                time_encode,
+                time_decode,
+            };
+
+            println!("{:?}", db_coding);
+        }
+    }
+}
+
+/// Bit error rate sweep, and other channel models at about 5% errors, with their names.
+fn channels() -> Vec<(Box<Channel>, String)> {
+    let mut channels: Vec<(Box<Channel>, String)> = NoiseLevel::steps()
+        .into_iter()
+        .filter(|level| level.to_f64() >= 0.001 && level.to_f64() <= 0.2)
+        .map(|level| (Box::new(level) as Box<Channel>, level.to_string()))
+        .collect();
+    channels.push((Box::new(GilbertElliott::bursts(0.05, 16.0)), "ge(0.05;16)".into()));
+    channels.push((Box::new(Erasure::new(0.05)), "erasure(0.05)".into()));
+    channels.push((Box::new(InsertionDeletion::new(0.001, 0.001)), "indel(0.001)".into()));
+    channels
+}
+
+/// Compare naive bit by bit Huffman decoder against the table-driven one.
+fn bench_huffman(content: &[u8]) -> Result<()> {
+    let huffman = Huffman::<u8>::canonical_for(content);
+    let compressed = huffman.compress(content)?;
+
+    let (bitwise, time_bitwise) = profile(|| {
+        Ok(huffman.decompress_bitwise(compressed.clone())?)
+    })?;
+    let (table, time_table) = profile(|| {
+        Ok(huffman.decompress(compressed.clone())?)
+    })?;
+    if bitwise != table || table != content {
+        return Err("huffman decoders disagree".into());
+    }
+
+    println!("huffman decompress: bitwise {} ms, table {} ms", time_bitwise, time_table);
+    Ok(())
+}
+
 fn run() -> Result<()> {
     db::create_schema()?;
 
@@ -104,6 +249,15 @@ fn run() -> Result<()> {
         file.read_to_end(&mut content)?;
         let content = Arc::new(content);  // non-mutable shared
 
+        {
+            let db_file = db_file.clone();
+            let content = Arc::clone(&content);
+            pool.execute(move || {
+                // baseline
+                let compression = NoCompression;
+                live_with_it("none", &compression, &*content, &db_file).unwrap();
+            });
+        }
         {
             let db_file = db_file.clone();
             let content = Arc::clone(&content);
@@ -128,6 +282,64 @@ fn run() -> Result<()> {
                 live_with_it("rle", &compression, &*content, &db_file).unwrap();
             });
         }
+        {
+            let db_file = db_file.clone();
+            let content = Arc::clone(&content);
+            pool.execute(move || {
+                let compression = AdaptiveHuffman;
+                live_with_it("adaptive", &compression, &*content, &db_file).unwrap();
+            });
+        }
+        {
+            let db_file = db_file.clone();
+            let content = Arc::clone(&content);
+            pool.execute(move || {
+                let compression = Arithmetic;
+                live_with_it("arithmetic", &compression, &*content, &db_file).unwrap();
+            });
+        }
+        {
+            let db_file = db_file.clone();
+            let content = Arc::clone(&content);
+            pool.execute(move || {
+                let compression = Lzss::default();
+                live_with_it("lzss", &compression, &*content, &db_file).unwrap();
+            });
+        }
+        {
+            let db_file = db_file.clone();
+            let content = Arc::clone(&content);
+            pool.execute(move || {
+                // DEFLATE-like
+                let compression = Pipeline::new(vec![
+                    Box::new(Lzss::default()),
+                    Box::new(CanonicalHuffman),
+                ]);
+                live_with_it("lzss+huffman", &compression, &*content, &db_file).unwrap();
+            });
+        }
+        {
+            let db_file = db_file.clone();
+            let content = Arc::clone(&content);
+            pool.execute(move || {
+                let compression = Lzw::default();
+                live_with_it("lzw", &compression, &*content, &db_file).unwrap();
+            });
+        }
+        {
+            let db_file = db_file.clone();
+            let content = Arc::clone(&content);
+            pool.execute(move || {
+                // bzip2-like
+                let compression = Pipeline::new(vec![
+                    Box::new(Bwt::default()),
+                    Box::new(Mtf),
+                    Box::new(Rle),
+                    Box::new(CanonicalHuffman),
+                ]);
+                live_with_it("bwt+mtf+rle+huffman", &compression, &*content, &db_file).unwrap();
+            });
+        }
     }
     pool.join();
     Ok(())
@@ -200,98 +412,14 @@ pub fn live_with_it(compression_name: &str,
         time_compress,
         time_decompress: None,
     };
-    //    c.save()?;
-
-    println!("{:?}", c);
-
-    return Ok(());
 
-    for &(coding, coding_name) in [
-        (&Repetition3 as &Coding, "r3"),
-        (&Repetition5, "r5"),
-        (&Parity, "parity"),
-        (&Hamming, "hamming")
-    ].iter() {
-        // println!("    coding: {}", coding_name);
-
-        let (encoded, time_encode) = profile(|| {
-            Ok(coding.encode(compressed.clone()))
-        })?;
-
-        // println!("    encoded: {:?}", &encoded);
-
-        let redundancy_rate = encoded.len() as f64 / (compressed.len()) as f64;
-
-        for noise in [
-            NoiseLevel::Noise001,
-            NoiseLevel::Noise005,
-            NoiseLevel::Noise015
-        ].iter() {
-            // println!("      nioSe: {:?}", noise);
-
-            let fucked_up: BitVec = noise.apply(encoded.iter()).collect();
-
-            // println!("  fucked up: {:?}", &fucked_up);
-
-            let ((decoded, stats), time_decode) = profile(move || {
-                Ok(coding.decode(fucked_up))
-            })?;
-
-            // println!("    decoded: {:?}", &decoded);
-            // println!("    det/cor: {}/{}", stats.detected, stats.corrected);
-
-            let dist = distance(&compressed, &decoded);
-
-            // println!("   distance: {}", dist);
-
-            let coding = db::Coding {
-                file_name: db_file.file_name.clone(),
-                compression: compression_name.into(),
-                coding_name: coding_name.into(),
-                noise_rate: noise.to_str().to_string(),
-                redundancy_rate,
-                size_decoded: compressed.len() as i64,
-                size_encoded: encoded.len() as i64,
-                corrected: stats.corrected as i64,
-                detected: stats.detected as i64,
-                not_corrected: dist as i64,
-                time_encode,
-                time_decode,
-            };
-            coding.save()?;
-
-            match profile(|| Ok(compression.decompress(decoded)?)) {
-                Ok((_decompressed, time_decompress)) => {
-                    if c.time_decompress == None {
-                        c.time_decompress = Some(time_decompress);
-                        c.save()?;
-                    }
-                }
-                Err(_) => {}
-            }
-
-            /*
-            match decompressed {
-                Ok(_) => {
-                    // let decompressed = BitVec::from_bytes(&vec);
-                    // // println!("decompressed {:?}", &decompressed);
-
-                    // let error = distance_vec(&original, &vec);
-                    // println!(" undetected: {}", error);
+    if let Ok((_decompressed, time_decompress)) = profile(|| Ok(compression.decompress(compressed)?)) {
+        c.time_decompress = Some(time_decompress);
+    }
+    c.save()?;
 
-                    // println!("result:");
-                    // std::io::stdout().write(&vec).unwrap();
-                }
-                Err(_) => {} // println!("decompress error: {:?}", e),
-            }
-            */
+    println!("{:?}", c);
 
-            std::io::stdout().write(b".")?;
-            std::io::stdout().flush()?;
-        }
-    }
-    std::io::stdout().write(b"\n")?;
-    std::io::stdout().flush()?;
     Ok(())
 }
 